@@ -1,4 +1,38 @@
 //! **PHOLIB** - Potentially Helpful Objects Library
 
+#![allow(clippy::needless_return, clippy::mut_from_ref)]
+
 mod multiref;
-pub use multiref::MultiRef;
+pub use multiref::{MultiRef, IndexError, reduce_into, pair_cyclic, merge_sorted, sync_if_different};
+mod multihooked;
+pub use multihooked::MultiHooked;
+#[cfg(feature = "history")]
+mod multihistory;
+#[cfg(feature = "history")]
+pub use multihistory::{MultiHistory, MultiHistoryMut};
+mod multibatched;
+pub use multibatched::{MultiBatched, MultiBatchedMut};
+mod projection;
+pub use projection::ProjectionPath;
+mod multisync;
+pub use multisync::MultiSync;
+mod multirc;
+pub use multirc::{MultiRc, MultiWeak};
+mod multiarc;
+pub use multiarc::{MultiArc, MultiArcWeak};
+mod multipin;
+pub use multipin::MultiPin;
+mod multibox;
+pub use multibox::MultiBox;
+mod multislice;
+pub use multislice::MultiSlice;
+mod multivec;
+pub use multivec::MultiVec;
+mod multimap;
+pub use multimap::MultiMap;
+mod multicell;
+pub use multicell::{MultiCell, MultiCellRef, MultiCellMut};
+mod multilazy;
+pub use multilazy::MultiLazy;
+mod zeroable;
+pub use zeroable::Zeroable;
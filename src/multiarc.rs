@@ -0,0 +1,272 @@
+use std::cell::UnsafeCell;
+use std::sync::{Arc, Weak};
+
+
+/// An atomically reference counted `MultiRef`. Cloning it creates another handle to the same value, not a copy,
+/// and handles can be sent to other threads.
+/// 
+/// # Broken Borrow Checker Rules
+/// 
+/// * [ ] Variables must be initialised before use.
+/// * [ ] Values can not be moved more than once.
+/// * [ ] Values can not be moved while borrowed.
+/// * [x] Values can not be accessed while mutably borrowed.
+/// * [x] Values can not be mutated while immutably borrowed.
+/// 
+/// # Generics
+/// 
+/// * `T` : The type of the wrapped value.
+/// 
+/// # Warning
+/// 
+/// * The handles can be shared across threads, but data races are still your responsibility.
+///   Two threads accessing the value at once, with at least one writing, is undefined behaviour.
+/// * You are responsible for preventing undefined behaviour.
+/// * IN MOST CASES THIS SHOULD NOT BE USED DUE TO THE UNPREDICTABLE AND DANGEROUS NATURE OF THIS SYSTEM.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiArc;
+/// let a = MultiArc::new(10);
+/// let b = a.clone();
+/// 
+/// unsafe {*b.get_mut() += 1};
+/// assert_eq!(unsafe {*a.get_ref()}, 11);
+/// ```
+/// 
+pub struct MultiArc<T>(Arc<UnsafeCell<T>>);
+
+unsafe impl<T : Send> Send for MultiArc<T> {}
+unsafe impl<T : Send> Sync for MultiArc<T> {}
+
+impl<T> MultiArc<T> {
+
+    /// Create a new `MultiArc` instance.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `object` : The object to wrap in the created `MultiArc`.
+    /// 
+    /// # Returns
+    /// 
+    /// The created `MultiArc` instance, the only handle to its value.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiArc;
+    /// let multiarc = MultiArc::new(10);
+    /// ```
+    /// 
+    pub fn new(object : T) -> MultiArc<T> {
+        return MultiArc(Arc::new(UnsafeCell::new(object)));
+    }
+
+    /// Get an immutable reference to the wrapped value.
+    /// Can be used simultaneously with `get_mut()`s or other `get_ref()`s, from this handle or any clone.
+    /// 
+    /// # Returns
+    /// 
+    /// An immutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The caller must make sure that the wrapped value is not being written to through another reference
+    /// while the returned reference is in use.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiArc;
+    /// let multiarc = MultiArc::new(10);
+    /// 
+    /// let i = unsafe {multiarc.get_ref()};
+    /// assert_eq!(*i, 10);
+    /// ```
+    /// 
+    pub unsafe fn get_ref(&self) -> &T {
+        return &*self.0.get();
+    }
+
+    /// Get a mutable reference to the wrapped value.
+    /// Can be used simultaneously with `get_ref()`s or other `get_mut()`s, from this handle or any clone.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The returned reference aliases every other reference obtained from this handle and its clones.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiArc;
+    /// let a = MultiArc::new(10);
+    /// let b = a.clone();
+    /// 
+    /// let x = unsafe {a.get_mut()};
+    /// let y = unsafe {b.get_mut()};
+    /// *x += 1;
+    /// *y += 2;
+    /// assert_eq!(unsafe {*a.get_ref()}, 13);
+    /// ```
+    /// 
+    pub unsafe fn get_mut(&self) -> &mut T {
+        return &mut *self.0.get();
+    }
+
+    /// Return the wrapped value if this is the only handle to it, like `Arc::try_unwrap`.
+    /// 
+    /// # Returns
+    /// 
+    /// `Ok` with the wrapped value, or `Err` with this handle if there are other clones alive.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiArc;
+    /// let a = MultiArc::new(10);
+    /// let b = a.clone();
+    /// 
+    /// let a = a.try_unwrap().unwrap_err();
+    /// drop(b);
+    /// assert_eq!(a.try_unwrap().ok(), Some(10));
+    /// ```
+    /// 
+    pub fn try_unwrap(self) -> Result<T, MultiArc<T>> {
+        return Arc::try_unwrap(self.0)
+            .map(UnsafeCell::into_inner)
+            .map_err(MultiArc);
+    }
+
+    /// Create a weak handle to the wrapped value, which does not keep it alive.
+    /// 
+    /// # Returns
+    /// 
+    /// A `MultiArcWeak` pointing to the same value.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiArc;
+    /// let multiarc = MultiArc::new(10);
+    /// 
+    /// let weak = multiarc.downgrade();
+    /// assert!(weak.upgrade().is_some());
+    /// drop(multiarc);
+    /// assert!(weak.upgrade().is_none());
+    /// ```
+    /// 
+    pub fn downgrade(&self) -> MultiArcWeak<T> {
+        return MultiArcWeak(Arc::downgrade(&self.0));
+    }
+
+}
+
+/// Create another handle to the same value. The value itself is not cloned.
+impl<T> Clone for MultiArc<T> {
+    fn clone(&self) -> MultiArc<T> {
+        return MultiArc(Arc::clone(&self.0));
+    }
+}
+
+
+
+
+
+
+/// A weak handle to the value of a `MultiArc`, created with `MultiArc::downgrade()`.
+/// It does not keep the value alive, and must be upgraded back into a `MultiArc` to access it.
+/// 
+/// # Generics
+/// 
+/// * `T` : The type of the wrapped value.
+/// 
+pub struct MultiArcWeak<T>(Weak<UnsafeCell<T>>);
+
+unsafe impl<T : Send> Send for MultiArcWeak<T> {}
+unsafe impl<T : Send> Sync for MultiArcWeak<T> {}
+
+impl<T> MultiArcWeak<T> {
+
+    /// Get a strong handle to the value, if it is still alive.
+    /// 
+    /// # Returns
+    /// 
+    /// A `MultiArc` to the value, or `None` if every `MultiArc` to it has been dropped.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiArc;
+    /// let multiarc = MultiArc::new(10);
+    /// 
+    /// let weak = multiarc.downgrade();
+    /// unsafe {*weak.upgrade().unwrap().get_mut() += 1};
+    /// assert_eq!(multiarc.try_unwrap().ok(), Some(11));
+    /// ```
+    /// 
+    pub fn upgrade(&self) -> Option<MultiArc<T>> {
+        return self.0.upgrade().map(MultiArc);
+    }
+
+}
+
+/// Create another weak handle to the same value.
+impl<T> Clone for MultiArcWeak<T> {
+    fn clone(&self) -> MultiArcWeak<T> {
+        return MultiArcWeak(Weak::clone(&self.0));
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::thread;
+
+    // Each thread is joined before the next one is spawned, so they never touch the counter at the same time.
+    #[test]
+    fn threads() {unsafe {
+        let counter = MultiArc::new(0);
+
+        thread::scope(|scope| {
+            for i in 1..=4 {
+                let handle = counter.clone();
+                scope.spawn(move || {
+                    for _ in 0..100 {
+                        *handle.get_mut() += i;
+                    }
+                }).join().unwrap();
+            }
+        });
+        assert_eq!(*counter.get_ref(), 1000);
+
+        let handle = counter.clone();
+        thread::spawn(move || *handle.get_mut() += 1).join().unwrap();
+        assert_eq!(counter.try_unwrap().ok(), Some(1001));
+    }}
+
+    #[test]
+    fn weak() {unsafe {
+        let strong = MultiArc::new(vec![1]);
+        let weak = strong.downgrade();
+        let other = weak.clone();
+
+        thread::spawn(move || other.upgrade().unwrap().get_mut().push(2)).join().unwrap();
+        assert_eq!(*strong.get_ref(), vec![1, 2]);
+
+        let upgraded = weak.upgrade().unwrap();
+        let strong = strong.try_unwrap().unwrap_err();
+        drop(upgraded);
+        assert_eq!(strong.try_unwrap().ok(), Some(vec![1, 2]));
+        assert!(weak.upgrade().is_none());
+    }}
+
+}
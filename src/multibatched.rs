@@ -0,0 +1,304 @@
+use std::cell::{Cell, RefCell};
+use std::ops::{Deref, DerefMut};
+
+use crate::MultiRef;
+
+
+/// A `MultiRef` that counts mutations made through `get_mut()`, and calls a registered callback with a clone of the value after every `batch` of them,
+/// e.g. to publish an `Arc` snapshot.
+/// 
+/// `get_mut()` returns a guard, and the mutation is counted when the guard is dropped, so the snapshot includes the changes made through it.
+/// Mutations are counted even while no callback is registered.
+/// 
+/// # Broken Borrow Checker Rules
+/// 
+/// * [ ] Variables must be initialised before use.
+/// * [ ] Values can not be moved more than once.
+/// * [ ] Values can not be moved while borrowed.
+/// * [x] Values can not be accessed while mutably borrowed.
+/// * [x] Values can not be mutated while immutably borrowed.
+/// 
+/// # Generics
+/// 
+/// * `'a` : The lifetime of the callback.
+/// * `T`  : The type of the wrapped value.
+/// 
+/// # Warning
+/// 
+/// * This structure is not thread safe.
+/// * You are responsible for preventing undefined behaviour.
+/// * IN MOST CASES THIS SHOULD NOT BE USED DUE TO THE UNPREDICTABLE AND DANGEROUS NATURE OF THIS SYSTEM.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// use std::sync::{Arc, Mutex};
+/// let published = Arc::new(Mutex::new(Arc::new(0)));
+/// let publisher = Arc::clone(&published);
+/// 
+/// let mut batched = MultiRef::new_batched(0, 3);
+/// batched.on_batch(move |value| *publisher.lock().unwrap() = Arc::new(value));
+/// 
+/// for _ in 0..7 {
+///     *unsafe {batched.get_mut()} += 1;
+/// }
+/// assert_eq!(**published.lock().unwrap(), 6);
+/// ```
+/// 
+pub struct MultiBatched<'a, T : Clone> {
+    value    : MultiRef<T>,
+    batch    : usize,
+    count    : Cell<usize>,
+    callback : RefCell<Option<Callback<'a, T>>>
+}
+
+type Callback<'a, T> = Box<dyn FnMut(T) + 'a>;
+
+impl<'a, T : Clone> MultiBatched<'a, T> {
+
+    /// Create a new `MultiBatched` instance with no callback registered. The same as `MultiRef::new_batched()`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `value` : The object to wrap in the created `MultiBatched`.
+    /// * `batch` : The number of mutations between calls to the callback.
+    /// 
+    /// # Returns
+    /// 
+    /// The created `MultiBatched` instance.
+    /// 
+    /// # Panics
+    /// 
+    /// If `batch` is `0`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiBatched;
+    /// let batched = MultiBatched::new_batched(10, 4);
+    /// ```
+    /// 
+    pub fn new_batched(value : T, batch : usize) -> MultiBatched<'a, T> {
+        assert!(batch > 0, "MultiBatched batch size must be greater than 0");
+        return MultiBatched {
+            value    : MultiRef::new(value),
+            batch,
+            count    : Cell::new(0),
+            callback : RefCell::new(None)
+        };
+    }
+
+    /// Register the callback, replacing the previous one if there was one.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `callback` : Called with a clone of the value after every `batch` mutations.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiBatched;
+    /// let mut snapshots = Vec::new();
+    /// let mut batched = MultiBatched::new_batched(0, 2);
+    /// batched.on_batch(|value| snapshots.push(value));
+    /// 
+    /// for _ in 0..4 {
+    ///     *unsafe {batched.get_mut()} += 1;
+    /// }
+    /// drop(batched);
+    /// assert_eq!(snapshots, vec![2, 4]);
+    /// ```
+    /// 
+    pub fn on_batch<F : FnMut(T) + 'a>(&mut self, callback : F) {
+        *self.callback.get_mut() = Some(Box::new(callback));
+    }
+
+    /// Get an immutable reference to the wrapped value. This is not counted.
+    /// Can be used simultaneously with `get_mut()`s or other `get_ref()`s.
+    /// 
+    /// # Returns
+    /// 
+    /// An immutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The caller must make sure that the wrapped value is not being written to through another reference or guard
+    /// while the returned reference is in use.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiBatched;
+    /// let batched = MultiBatched::new_batched(10, 4);
+    /// 
+    /// assert_eq!(unsafe {*batched.get_ref()}, 10);
+    /// ```
+    /// 
+    pub unsafe fn get_ref(&self) -> &T {
+        return self.value.get_ref();
+    }
+
+    /// Get a guard that gives mutable access to the wrapped value. The mutation is counted when the guard is dropped,
+    /// and every `batch`-th one calls the callback.
+    /// Can be used simultaneously with `get_ref()`s or other `get_mut()`s.
+    /// 
+    /// # Returns
+    /// 
+    /// A guard that derefs mutably to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// References obtained through the guard alias every other reference obtained from this `MultiBatched`.
+    /// The value may be cloned when the guard is dropped, so it must not be being written to through another reference at that point.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiBatched;
+    /// let batched = MultiBatched::new_batched(10, 4);
+    /// 
+    /// let mut a = unsafe {batched.get_mut()};
+    /// let mut b = unsafe {batched.get_mut()};
+    /// *a += 1;
+    /// *b += 2;
+    /// drop(a);
+    /// drop(b);
+    /// assert_eq!(batched.mutation_count(), 2);
+    /// ```
+    /// 
+    pub unsafe fn get_mut(&self) -> MultiBatchedMut<'_, 'a, T> {
+        return MultiBatchedMut(self);
+    }
+
+    /// Get the number of mutations counted so far.
+    /// 
+    /// # Returns
+    /// 
+    /// The number of `get_mut()` guards that have been dropped.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiBatched;
+    /// let batched = MultiBatched::new_batched(10, 4);
+    /// 
+    /// *unsafe {batched.get_mut()} += 1;
+    /// assert_eq!(batched.mutation_count(), 1);
+    /// ```
+    /// 
+    pub fn mutation_count(&self) -> usize {
+        return self.count.get();
+    }
+
+    /// Return the wrapped value and drop the `MultiBatched` and its callback.
+    /// The callback is not called for an unfinished batch.
+    /// 
+    /// # Returns
+    /// 
+    /// The value that was wrapped.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiBatched;
+    /// let batched = MultiBatched::new_batched(10, 4);
+    /// 
+    /// assert_eq!(batched.unwrap(), 10);
+    /// ```
+    /// 
+    pub fn unwrap(self) -> T {
+        return self.value.unwrap();
+    }
+
+    fn count(&self) {
+        let count = self.count.get() + 1;
+        self.count.set(count);
+        if count % self.batch == 0 {
+            if let Some(callback) = self.callback.borrow_mut().as_mut() {
+                callback(unsafe {self.value.get_ref()}.clone());
+            }
+        }
+    }
+
+}
+
+
+/// A guard giving mutable access to the value of a `MultiBatched`, created with `MultiBatched::get_mut()`.
+/// Counts the mutation when dropped.
+/// 
+/// # Generics
+/// 
+/// * `'b` : The lifetime of the borrow of the `MultiBatched`.
+/// * `'a` : The lifetime of the callback.
+/// * `T`  : The type of the wrapped value.
+/// 
+pub struct MultiBatchedMut<'b, 'a, T : Clone>(&'b MultiBatched<'a, T>);
+
+impl<T : Clone> Deref for MultiBatchedMut<'_, '_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        return unsafe {self.0.value.get_ref()};
+    }
+}
+
+impl<T : Clone> DerefMut for MultiBatchedMut<'_, '_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        return unsafe {self.0.value.get_mut()};
+    }
+}
+
+impl<T : Clone> Drop for MultiBatchedMut<'_, '_, T> {
+    fn drop(&mut self) {
+        self.0.count();
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::panic;
+
+    #[test]
+    fn batches() {unsafe {
+        let snapshots = RefCell::new(Vec::new());
+        let mut batched = MultiRef::new_batched(String::new(), 3);
+        batched.on_batch(|value| snapshots.borrow_mut().push(value));
+
+        for (i, letter) in "abcdefgh".chars().enumerate() {
+            batched.get_mut().push(letter);
+            // Fires on the 3rd and 6th mutation only, with the value after that mutation.
+            let expected = match i + 1 {
+                1..=2 => vec![],
+                3..=5 => vec!["abc"],
+                _     => vec!["abc", "abcdef"]
+            };
+            assert_eq!(*snapshots.borrow(), expected);
+        }
+        assert_eq!(batched.mutation_count(), 8);
+
+        // Reads are not counted.
+        assert_eq!(batched.get_ref(), "abcdefgh");
+        assert_eq!(batched.mutation_count(), 8);
+        assert_eq!(batched.unwrap(), "abcdefgh");
+        assert_eq!(snapshots.into_inner(), vec!["abc", "abcdef"]);
+
+        // Mutations before the callback is registered still count towards the batch.
+        let calls = Cell::new(0);
+        let mut batched = MultiBatched::new_batched(0, 2);
+        *batched.get_mut() += 1;
+        batched.on_batch(|value| {
+            calls.set(calls.get() + 1);
+            assert_eq!(value, 2);
+        });
+        *batched.get_mut() += 1;
+        assert_eq!(calls.get(), 1);
+
+        assert!(panic::catch_unwind(|| MultiBatched::new_batched(0, 0)).is_err());
+    }}
+
+}
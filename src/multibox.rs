@@ -0,0 +1,475 @@
+use std::alloc::{self, Layout};
+use std::cell::UnsafeCell;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::ptr::NonNull;
+
+use crate::{MultiRef, Zeroable};
+
+
+/// A `MultiRef` that keeps its value on the heap. It also works with unsized values such as `str`, `[T]` and trait objects.
+/// 
+/// # Broken Borrow Checker Rules
+/// 
+/// * [ ] Variables must be initialised before use.
+/// * [ ] Values can not be moved more than once.
+/// * [ ] Values can not be moved while borrowed.
+/// * [x] Values can not be accessed while mutably borrowed.
+/// * [x] Values can not be mutated while immutably borrowed.
+/// 
+/// # Generics
+/// 
+/// * `T` : The type of the wrapped value.
+/// 
+/// # Address Stability
+/// 
+/// Moving a `MultiBox` only moves the pointer to its allocation, not the wrapped value,
+/// so the value stays at the same address until the `MultiBox` is dropped or unwrapped.
+/// A pointer taken with `as_ptr()` or `as_mut_ptr()` can still be used after the `MultiBox` has been moved, e.g. into a `Vec`.
+/// 
+/// `new()` takes the value by argument, so it is still created on the stack and then moved to the heap.
+/// Large buffers should be created with `from_box()`, `zeroed()` or `new_zeroed()` instead.
+/// 
+/// # Warning
+/// 
+/// * This structure is not thread safe.
+/// * You are responsible for preventing undefined behaviour.
+/// * IN MOST CASES THIS SHOULD NOT BE USED DUE TO THE UNPREDICTABLE AND DANGEROUS NATURE OF THIS SYSTEM.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiBox;
+/// let multibox = MultiBox::<[u8; 65536]>::zeroed();
+/// 
+/// let a = unsafe {multibox.get_ref()};
+/// let b = unsafe {multibox.get_mut()};
+/// b[0] = 1;
+/// assert_eq!(a[0], 1);
+/// ```
+/// 
+pub struct MultiBox<T : ?Sized>(Box<UnsafeCell<T>>);
+
+impl<T> MultiBox<T> {
+
+    /// Create a new `MultiBox` instance, moving the value onto the heap.
+    /// The value is passed by argument, so it exists on the stack first. See `from_box()` and `zeroed()` for large values.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `object` : The object to wrap in the created `MultiBox`.
+    /// 
+    /// # Returns
+    /// 
+    /// The created `MultiBox` instance.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiBox;
+    /// let multibox = MultiBox::new(10);
+    /// ```
+    /// 
+    pub fn new(object : T) -> MultiBox<T> {
+        return MultiBox(Box::new(UnsafeCell::new(object)));
+    }
+
+    /// Return the wrapped value and drop the `MultiBox`, moving the value off the heap.
+    /// 
+    /// # Returns
+    /// 
+    /// The value that was wrapped.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiBox;
+    /// let multibox = MultiBox::new(10);
+    /// 
+    /// assert_eq!(multibox.unwrap(), 10);
+    /// ```
+    /// 
+    pub fn unwrap(self) -> T {
+        return self.0.into_inner();
+    }
+
+    /// Create a `MultiBox` whose value has every byte set to zero, allocated directly on the heap.
+    /// Unlike `MultiBox::new()` and `MultiRef::new_zeroed()`, the value never exists on the stack,
+    /// so this works for buffers that are too large for it.
+    /// 
+    /// # Returns
+    /// 
+    /// A `MultiBox` wrapping an all-zero `T`.
+    /// 
+    /// # Safety
+    /// 
+    /// All-zero bytes must be a valid `T`, the same as with `MultiRef::new_zeroed()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiBox;
+    /// let buffer = unsafe {MultiBox::<[u8; 1 << 24]>::new_zeroed()};
+    /// 
+    /// assert!(unsafe {buffer.get_ref()}.iter().all(|byte| *byte == 0));
+    /// ```
+    /// 
+    pub unsafe fn new_zeroed() -> MultiBox<T> {
+        let layout = Layout::new::<UnsafeCell<T>>();
+        if layout.size() == 0 {
+            // Zero-sized boxes do not allocate, and only need a dangling, aligned pointer.
+            return MultiBox(Box::from_raw(NonNull::dangling().as_ptr()));
+        }
+        let ptr = alloc::alloc_zeroed(layout) as *mut UnsafeCell<T>;
+        if ptr.is_null() {
+            alloc::handle_alloc_error(layout);
+        }
+        // The allocation comes from the global allocator with the layout of `UnsafeCell<T>`, as `Box` expects.
+        return MultiBox(Box::from_raw(ptr));
+    }
+
+    /// Create a `MultiBox` whose value has every byte set to zero, for types that are marked as `Zeroable`.
+    /// This is the safe version of `new_zeroed()`.
+    /// 
+    /// # Returns
+    /// 
+    /// A `MultiBox` wrapping an all-zero `T`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiBox;
+    /// let buffer = MultiBox::<[u8; 65536]>::zeroed();
+    /// 
+    /// unsafe {buffer.get_mut()[0] = 1};
+    /// assert_eq!(buffer.into_box()[..2], [1, 0]);
+    /// ```
+    /// 
+    pub fn zeroed() -> MultiBox<T> where T : Zeroable {
+        return unsafe {MultiBox::new_zeroed()};
+    }
+
+}
+
+impl<T : ?Sized> MultiBox<T> {
+
+    /// Create a `MultiBox` that takes over an existing allocation, without moving the value.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `boxed` : The box holding the value.
+    /// 
+    /// # Returns
+    /// 
+    /// A `MultiBox` wrapping the value in the same allocation.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiBox;
+    /// let multibox = MultiBox::<str>::from_box(Box::from("text"));
+    /// 
+    /// assert_eq!(unsafe {multibox.get_ref()}, "text");
+    /// ```
+    /// 
+    pub fn from_box(boxed : Box<T>) -> MultiBox<T> {
+        // `UnsafeCell<T>` has the same layout as `T`, so the allocation and pointer metadata carry over.
+        return MultiBox(unsafe {Box::from_raw(Box::into_raw(boxed) as *mut UnsafeCell<T>)});
+    }
+
+    /// Turn the `MultiBox` back into a plain `Box`, without moving the value.
+    /// 
+    /// # Returns
+    /// 
+    /// A box holding the wrapped value in the same allocation.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiBox;
+    /// let multibox = MultiBox::<[i32]>::from_box(vec![1, 2].into_boxed_slice());
+    /// 
+    /// assert_eq!(*multibox.into_box(), [1, 2]);
+    /// ```
+    /// 
+    pub fn into_box(self) -> Box<T> {
+        return unsafe {Box::from_raw(Box::into_raw(self.0) as *mut T)};
+    }
+
+    /// Get an immutable reference to the wrapped value.
+    /// Can be used simultaneously with `get_mut()`s or other `get_ref()`s.
+    /// 
+    /// # Returns
+    /// 
+    /// An immutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The caller must make sure that the wrapped value is not being written to through another reference
+    /// while the returned reference is in use.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiBox;
+    /// let multibox = MultiBox::new(10);
+    /// 
+    /// let i = unsafe {multibox.get_ref()};
+    /// assert_eq!(*i, 10);
+    /// ```
+    /// 
+    pub unsafe fn get_ref(&self) -> &T {
+        return &*self.0.get();
+    }
+
+    /// Get a mutable reference to the wrapped value.
+    /// Can be used simultaneously with `get_ref()`s or other `get_mut()`s.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The returned reference aliases every other reference obtained from this `MultiBox`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiBox;
+    /// let multibox = MultiBox::<str>::from_box(Box::from("text"));
+    /// 
+    /// unsafe {multibox.get_mut().make_ascii_uppercase()};
+    /// assert_eq!(unsafe {multibox.get_ref()}, "TEXT");
+    /// ```
+    /// 
+    pub unsafe fn get_mut(&self) -> &mut T {
+        return &mut *self.0.get();
+    }
+
+    /// Get a pinned mutable reference to the wrapped value, for APIs that take `Pin<&mut T>` (e.g. `Future::poll`).
+    /// Moving the `MultiBox` does not move the value, so this is safe to use with `!Unpin` values.
+    /// 
+    /// # Returns
+    /// 
+    /// A pinned mutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The same as `get_mut()`. Unless `T` is `Unpin`, the value must also not be moved out with `unwrap()` or through
+    /// `get_mut()` (e.g. with `mem::swap`) once this has been called.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiBox;
+    /// use std::future::Future;
+    /// use std::task::{Context, Poll, Waker};
+    /// let multibox = MultiBox::new(async {10});
+    /// 
+    /// let mut context = Context::from_waker(Waker::noop());
+    /// let poll = unsafe {multibox.get_pin_mut()}.poll(&mut context);
+    /// assert_eq!(poll, Poll::Ready(10));
+    /// ```
+    /// 
+    pub unsafe fn get_pin_mut(&self) -> Pin<&mut T> {
+        return Pin::new_unchecked(self.get_mut());
+    }
+
+    /// Get a raw pointer to the wrapped value, which stays valid when the `MultiBox` is moved.
+    /// 
+    /// # Returns
+    /// 
+    /// A raw pointer to the wrapped value. Dereferencing it is up to the caller.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiBox;
+    /// let multibox = MultiBox::new(10);
+    /// 
+    /// let ptr = multibox.as_ptr();
+    /// let moved = vec![multibox];
+    /// assert_eq!(unsafe {*ptr}, *unsafe {moved[0].get_ref()});
+    /// ```
+    /// 
+    pub fn as_ptr(&self) -> *const T {
+        return self.0.get();
+    }
+
+    /// Get a mutable raw pointer to the wrapped value, which stays valid when the `MultiBox` is moved.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable raw pointer to the wrapped value. Dereferencing it is up to the caller.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiBox;
+    /// let multibox = MultiBox::new(10);
+    /// 
+    /// let ptr = multibox.as_mut_ptr();
+    /// let moved = vec![multibox];
+    /// unsafe {*ptr += 1};
+    /// assert_eq!(unsafe {*moved[0].get_ref()}, 11);
+    /// ```
+    /// 
+    pub fn as_mut_ptr(&self) -> *mut T {
+        return self.0.get();
+    }
+
+}
+
+/// Dereferences to the wrapped value, the same as `MultiRef`.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiBox;
+/// let multibox = MultiBox::new(vec![1, 2]);
+/// 
+/// unsafe {multibox.get_mut()}.push(3);
+/// assert_eq!(multibox.len(), 3);
+/// ```
+/// 
+impl<T : ?Sized> Deref for MultiBox<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        return unsafe {self.get_ref()};
+    }
+}
+
+/// The wrapped value lives in its own allocation, so its address does not change when the `MultiBox` is moved.
+/// 
+/// Only available with the `stable_deref_trait` feature.
+/// 
+#[cfg(feature = "stable_deref_trait")]
+unsafe impl<T : ?Sized> stable_deref_trait::StableDeref for MultiBox<T> {}
+
+/// Move the value of a `MultiRef` onto the heap.
+impl<T> From<MultiRef<T>> for MultiBox<T> {
+    fn from(multiref : MultiRef<T>) -> MultiBox<T> {
+        return MultiBox::new(multiref.unwrap());
+    }
+}
+
+/// Move the value of a `MultiBox` off the heap into a `MultiRef`.
+impl<T> From<MultiBox<T>> for MultiRef<T> {
+    fn from(multibox : MultiBox<T>) -> MultiRef<T> {
+        return MultiRef::new(multibox.unwrap());
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn stable_address() {unsafe {
+        let multibox = MultiBox::new(vec![1]);
+        let early = &mut *multibox.as_mut_ptr();
+
+        let mut owners = Vec::new();
+        owners.push(multibox);
+        owners.reserve(100);
+        early.push(2);
+        owners[0].get_mut().push(3);
+        assert_eq!(*early, vec![1, 2, 3]);
+
+        let multiref : MultiRef<Vec<i32>> = owners.pop().unwrap().into();
+        assert_eq!(multiref.unwrap(), vec![1, 2, 3]);
+    }}
+
+    #[test]
+    fn unsized_values() {unsafe {
+        let text = MultiBox::<str>::from_box(Box::from("hello"));
+        text.get_mut().make_ascii_uppercase();
+        assert_eq!(text.get_ref(), "HELLO");
+        assert_eq!(&*text.into_box(), "HELLO");
+
+        let slice = MultiBox::<[i32]>::from_box(Box::new([3, 1, 2]));
+        slice.get_mut().sort();
+        assert_eq!(slice.get_ref(), [1, 2, 3]);
+        slice.get_mut()[0] = 10;
+        assert_eq!(slice.get_ref(), [10, 2, 3]);
+        assert_eq!(slice.get_ref().len(), 3);
+        assert_eq!(slice.into_box(), Box::from([10, 2, 3]));
+
+        let sized = MultiBox::from(MultiRef::new(String::from("a")));
+        sized.get_mut().push('b');
+        assert_eq!(*sized.into_box(), "ab");
+    }}
+
+    #[test]
+    fn trait_objects() {unsafe {
+        let calls = Cell::new(0);
+        let callback = MultiBox::<dyn Fn() -> i32>::from_box(Box::new(|| {
+            calls.set(calls.get() + 1);
+            return calls.get();
+        }));
+        let a = callback.get_ref();
+        let b = callback.get_mut();
+        assert_eq!(a(), 1);
+        assert_eq!(b(), 2);
+        assert_eq!(callback.into_box()(), 3);
+
+        let mut total = 0;
+        let counter = MultiBox::<dyn FnMut(i32) + '_>::from_box(Box::new(|x| total += x));
+        let a = counter.get_mut();
+        let b = counter.get_mut();
+        a(1);
+        b(2);
+        a(3);
+        drop(counter);
+        assert_eq!(total, 6);
+    }}
+
+    #[test]
+    fn zeroed() {unsafe {
+        // Far larger than a test thread's stack, so this only passes if the value is zeroed on the heap.
+        let buffer = MultiBox::<[u64; 1 << 22]>::zeroed();
+        let ptr = buffer.as_ptr();
+        assert!(buffer.get_ref().iter().all(|word| *word == 0));
+        buffer.get_mut()[(1 << 22) - 1] = 7;
+        assert_eq!((*ptr)[(1 << 22) - 1], 7);
+        assert_eq!(buffer.into_box().iter().sum::<u64>(), 7);
+
+        let point = MultiBox::<(i32, f64, char)>::new_zeroed();
+        assert_eq!(*point.get_ref(), (0, 0.0, '\0'));
+        assert_eq!(MultiBox::<()>::zeroed().unwrap(), ());
+    }}
+
+    #[cfg(feature = "stable_deref_trait")]
+    #[test]
+    fn stable_deref() {unsafe {
+        use stable_deref_trait::StableDeref;
+
+        // A minimal owning reference: keeps the owner alive next to a pointer into it.
+        struct OwningRef<O : StableDeref, U : ?Sized> {
+            owner : O,
+            part  : *const U
+        }
+        impl<O : StableDeref, U : ?Sized> OwningRef<O, U> {
+            fn new(owner : O, f : impl FnOnce(&O::Target) -> &U) -> OwningRef<O, U> {
+                let part = f(&owner) as *const U;
+                return OwningRef {owner, part};
+            }
+            fn get(&self) -> &U {
+                return unsafe {&*self.part};
+            }
+        }
+
+        let owning = OwningRef::new(MultiBox::new(vec![1, 2, 3]), |v| &v[1..]);
+        let moved = [owning];
+        assert_eq!(moved[0].get(), [2, 3]);
+        moved[0].owner.get_mut()[2] = 30;
+        assert_eq!(*moved[0].part, [2, 30]);
+    }}
+
+}
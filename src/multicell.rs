@@ -0,0 +1,388 @@
+use std::cell::{Cell, UnsafeCell};
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+
+
+/// A `MultiRef` that hands out references as guards, so it knows whether any are still alive.
+/// 
+/// Guards keep the value alive on their own, without borrowing the `MultiCell`.
+/// This means the `MultiCell` can be moved or unwrapped while guards exist, and `try_unwrap()` can check for them at runtime.
+/// 
+/// Unlike `RefCell`, a borrow is never refused by default. The number of live guards is reported by `ref_count()` and `mut_count()`,
+/// so tests can assert their own invariants. Guards are counted down when dropped, including while unwinding from a panic.
+/// A limit on the number of live `get_ref()` guards can be set with `set_max_refs()`, to enforce an aliasing budget during development.
+/// 
+/// # Broken Borrow Checker Rules
+/// 
+/// * [ ] Variables must be initialised before use.
+/// * [ ] Values can not be moved more than once.
+/// * [ ] Values can not be moved while borrowed.
+/// * [x] Values can not be accessed while mutably borrowed.
+/// * [x] Values can not be mutated while immutably borrowed.
+/// 
+/// # Generics
+/// 
+/// * `T` : The type of the wrapped value.
+/// 
+/// # Warning
+/// 
+/// * This structure is not thread safe.
+/// * You are responsible for preventing undefined behaviour.
+/// * IN MOST CASES THIS SHOULD NOT BE USED DUE TO THE UNPREDICTABLE AND DANGEROUS NATURE OF THIS SYSTEM.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiCell;
+/// let multicell = MultiCell::new(10);
+/// 
+/// let mut guard = unsafe {multicell.get_mut()};
+/// *guard += 1;
+/// let multicell = multicell.try_unwrap().unwrap_err();
+/// 
+/// drop(guard);
+/// assert_eq!(multicell.try_unwrap().ok(), Some(11));
+/// ```
+/// 
+pub struct MultiCell<T>(Rc<Shared<T>>);
+
+struct Shared<T> {
+    value    : UnsafeCell<T>,
+    refs     : Cell<usize>,
+    muts     : Cell<usize>,
+    max_refs : Cell<usize>
+}
+
+impl<T> MultiCell<T> {
+
+    /// Create a new `MultiCell` instance.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `object` : The object to wrap in the created `MultiCell`.
+    /// 
+    /// # Returns
+    /// 
+    /// The created `MultiCell` instance.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiCell;
+    /// let multicell = MultiCell::new(10);
+    /// ```
+    /// 
+    pub fn new(object : T) -> MultiCell<T> {
+        return MultiCell(Rc::new(Shared {
+            value    : UnsafeCell::new(object),
+            refs     : Cell::new(0),
+            muts     : Cell::new(0),
+            max_refs : Cell::new(usize::MAX)
+        }));
+    }
+
+    /// Get a guard that gives immutable access to the wrapped value.
+    /// Can be used simultaneously with `get_mut()`s or other `get_ref()`s.
+    /// 
+    /// # Returns
+    /// 
+    /// A guard that derefs to the wrapped value.
+    /// 
+    /// # Panics
+    /// 
+    /// If there are already as many live `get_ref()` guards as the limit set with `set_max_refs()`.
+    /// 
+    /// # Safety
+    /// 
+    /// The caller must make sure that the wrapped value is not being written to through another guard or reference
+    /// while the guard is dereferenced.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiCell;
+    /// let multicell = MultiCell::new(10);
+    /// 
+    /// let guard = unsafe {multicell.get_ref()};
+    /// assert_eq!(*guard, 10);
+    /// ```
+    /// 
+    pub unsafe fn get_ref(&self) -> MultiCellRef<T> {
+        let refs = self.0.refs.get();
+        assert!(refs < self.0.max_refs.get(), "MultiCell already has {} live get_ref() guards, the limit set with set_max_refs()", refs);
+        self.0.refs.set(refs + 1);
+        return MultiCellRef(Rc::clone(&self.0));
+    }
+
+    /// Get a guard that gives mutable access to the wrapped value.
+    /// Can be used simultaneously with `get_ref()`s or other `get_mut()`s.
+    /// 
+    /// # Returns
+    /// 
+    /// A guard that derefs mutably to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// References obtained through the guard alias every other reference obtained from this `MultiCell`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiCell;
+    /// let multicell = MultiCell::new(10);
+    /// 
+    /// let mut a = unsafe {multicell.get_mut()};
+    /// let mut b = unsafe {multicell.get_mut()};
+    /// *a += 1;
+    /// *b += 2;
+    /// assert_eq!(*unsafe {multicell.get_ref()}, 13);
+    /// ```
+    /// 
+    pub unsafe fn get_mut(&self) -> MultiCellMut<T> {
+        self.0.muts.set(self.0.muts.get() + 1);
+        return MultiCellMut(Rc::clone(&self.0));
+    }
+
+    /// Return the wrapped value, but only if no guards are alive.
+    /// 
+    /// # Returns
+    /// 
+    /// `Ok` with the wrapped value, or `Err` with this `MultiCell` if there are guards alive. It can be used as before.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiCell;
+    /// let multicell = MultiCell::new(10);
+    /// 
+    /// let guard = unsafe {multicell.get_ref()};
+    /// let multicell = multicell.try_unwrap().unwrap_err();
+    /// drop(guard);
+    /// assert_eq!(multicell.try_unwrap().ok(), Some(10));
+    /// ```
+    /// 
+    pub fn try_unwrap(self) -> Result<T, MultiCell<T>> {
+        return Rc::try_unwrap(self.0)
+            .map(|shared| shared.value.into_inner())
+            .map_err(MultiCell);
+    }
+
+    /// Get the number of `get_ref()` guards that are alive.
+    /// 
+    /// # Returns
+    /// 
+    /// The number of live immutable guards.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiCell;
+    /// let multicell = MultiCell::new(10);
+    /// 
+    /// let guard = unsafe {multicell.get_ref()};
+    /// assert_eq!(multicell.ref_count(), 1);
+    /// drop(guard);
+    /// assert_eq!(multicell.ref_count(), 0);
+    /// ```
+    /// 
+    pub fn ref_count(&self) -> usize {
+        return self.0.refs.get();
+    }
+
+    /// Get the number of `get_mut()` guards that are alive.
+    /// 
+    /// # Returns
+    /// 
+    /// The number of live mutable guards.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiCell;
+    /// let multicell = MultiCell::new(10);
+    /// 
+    /// let _a = unsafe {multicell.get_mut()};
+    /// let _b = unsafe {multicell.get_mut()};
+    /// assert_eq!(multicell.mut_count(), 2);
+    /// ```
+    /// 
+    pub fn mut_count(&self) -> usize {
+        return self.0.muts.get();
+    }
+
+    /// Set the maximum number of `get_ref()` guards that can be alive at once. Creating one more panics.
+    /// Guards that are already alive are not affected, even if there are more of them than the new limit.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `limit` : The maximum number of live immutable guards. There is no limit by default.
+    /// 
+    /// # Examples
+    /// 
+    /// ```should_panic
+    /// use pholib::MultiCell;
+    /// let multicell = MultiCell::new(10);
+    /// multicell.set_max_refs(1);
+    /// 
+    /// let a = unsafe {multicell.get_ref()};
+    /// let b = unsafe {multicell.get_ref()};
+    /// ```
+    /// 
+    pub fn set_max_refs(&self, limit : usize) {
+        self.0.max_refs.set(limit);
+    }
+
+}
+
+
+/// A guard giving immutable access to the value of a `MultiCell`, created with `MultiCell::get_ref()`.
+/// 
+/// # Generics
+/// 
+/// * `T` : The type of the wrapped value.
+/// 
+pub struct MultiCellRef<T>(Rc<Shared<T>>);
+
+impl<T> Deref for MultiCellRef<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        return unsafe {&*self.0.value.get()};
+    }
+}
+
+impl<T> Drop for MultiCellRef<T> {
+    fn drop(&mut self) {
+        self.0.refs.set(self.0.refs.get() - 1);
+    }
+}
+
+
+/// A guard giving mutable access to the value of a `MultiCell`, created with `MultiCell::get_mut()`.
+/// 
+/// # Generics
+/// 
+/// * `T` : The type of the wrapped value.
+/// 
+pub struct MultiCellMut<T>(Rc<Shared<T>>);
+
+impl<T> Deref for MultiCellMut<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        return unsafe {&*self.0.value.get()};
+    }
+}
+
+impl<T> DerefMut for MultiCellMut<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        return unsafe {&mut *self.0.value.get()};
+    }
+}
+
+impl<T> Drop for MultiCellMut<T> {
+    fn drop(&mut self) {
+        self.0.muts.set(self.0.muts.get() - 1);
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[test]
+    fn try_unwrap() {unsafe {
+        let multicell = MultiCell::new(vec![1]);
+        let reader = multicell.get_ref();
+        let mut writer = multicell.get_mut();
+        writer.push(2);
+        assert_eq!(*reader, vec![1, 2]);
+
+        // Fails while guards are alive, and the cell can still be used afterwards.
+        let multicell = multicell.try_unwrap().unwrap_err();
+        drop(writer);
+        let multicell = multicell.try_unwrap().unwrap_err();
+        multicell.get_mut().push(3);
+        assert_eq!(*reader, vec![1, 2, 3]);
+
+        // A guard outliving the owner keeps the value alive.
+        drop(multicell);
+        assert_eq!(*reader, vec![1, 2, 3]);
+        drop(reader);
+
+        let multicell = MultiCell::new(String::from("a"));
+        {
+            let mut guard = multicell.get_mut();
+            guard.push('b');
+        }
+        assert_eq!(multicell.try_unwrap().ok(), Some(String::from("ab")));
+    }}
+
+
+    #[test]
+    fn counts() {unsafe {
+        let multicell = MultiCell::new(0);
+        assert_eq!((multicell.ref_count(), multicell.mut_count()), (0, 0));
+
+        let a = multicell.get_ref();
+        let b = multicell.get_ref();
+        {
+            let mut c = multicell.get_mut();
+            *c += 1;
+            assert_eq!((multicell.ref_count(), multicell.mut_count()), (2, 1));
+            let _d = multicell.get_mut();
+            assert_eq!(multicell.mut_count(), 2);
+        }
+        assert_eq!((multicell.ref_count(), multicell.mut_count()), (2, 0));
+        drop(a);
+        assert_eq!(multicell.ref_count(), 1);
+        assert_eq!(*b, 1);
+        drop(b);
+        assert_eq!((multicell.ref_count(), multicell.mut_count()), (0, 0));
+    }}
+
+    #[test]
+    fn counts_after_panic() {unsafe {
+        let multicell = MultiCell::new(vec![1]);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _reader = multicell.get_ref();
+            let mut writer = multicell.get_mut();
+            writer.push(2);
+            assert_eq!((multicell.ref_count(), multicell.mut_count()), (1, 1));
+            panic!("guards are dropped while unwinding");
+        }));
+        assert!(result.is_err());
+        assert_eq!((multicell.ref_count(), multicell.mut_count()), (0, 0));
+        assert_eq!(multicell.try_unwrap().ok(), Some(vec![1, 2]));
+    }}
+
+    #[test]
+    fn max_refs_within_limit() {unsafe {
+        let multicell = MultiCell::new(0);
+        multicell.set_max_refs(2);
+
+        let a = multicell.get_ref();
+        let _b = multicell.get_ref();
+        // Mutable guards do not count towards the limit.
+        let _c = multicell.get_mut();
+        drop(a);
+        let _d = multicell.get_ref();
+        assert_eq!(multicell.ref_count(), 2);
+    }}
+
+    #[test]
+    #[should_panic(expected = "MultiCell already has 2 live get_ref() guards, the limit set with set_max_refs()")]
+    fn max_refs_exceeded() {unsafe {
+        let multicell = MultiCell::new(0);
+        multicell.set_max_refs(2);
+
+        let _a = multicell.get_ref();
+        let _b = multicell.get_ref();
+        let _c = multicell.get_ref();
+    }}
+
+}
@@ -0,0 +1,251 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::ops::{Deref, DerefMut};
+
+use crate::MultiRef;
+
+
+/// A `MultiRef` that records a clone of the wrapped value after each mutation made through `get_mut()`, for debugging value history.
+/// Only the most recent values are kept, up to the capacity given on creation. Older values are dropped first.
+/// 
+/// `get_mut()` returns a guard, and the value is recorded when the guard is dropped, so the record includes the changes made through it.
+/// 
+/// Only available with the `history` feature.
+/// 
+/// # Broken Borrow Checker Rules
+/// 
+/// * [ ] Variables must be initialised before use.
+/// * [ ] Values can not be moved more than once.
+/// * [ ] Values can not be moved while borrowed.
+/// * [x] Values can not be accessed while mutably borrowed.
+/// * [x] Values can not be mutated while immutably borrowed.
+/// 
+/// # Generics
+/// 
+/// * `T` : The type of the wrapped value.
+/// 
+/// # Warning
+/// 
+/// * This structure is not thread safe.
+/// * You are responsible for preventing undefined behaviour.
+/// * IN MOST CASES THIS SHOULD NOT BE USED DUE TO THE UNPREDICTABLE AND DANGEROUS NATURE OF THIS SYSTEM.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiHistory;
+/// let multihistory = MultiHistory::new(1, 2);
+/// 
+/// for _ in 0..3 {
+///     *unsafe {multihistory.get_mut()} *= 10;
+/// }
+/// assert_eq!(multihistory.history(), vec![100, 1000]);
+/// ```
+/// 
+pub struct MultiHistory<T : Clone> {
+    value    : MultiRef<T>,
+    history  : RefCell<VecDeque<T>>,
+    capacity : usize
+}
+
+impl<T : Clone> MultiHistory<T> {
+
+    /// Create a new `MultiHistory` instance with an empty history. The same as `MultiRef::new_with_history()`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `object`   : The object to wrap in the created `MultiHistory`.
+    /// * `capacity` : The number of recent values to keep. If it is `0`, nothing is recorded.
+    /// 
+    /// # Returns
+    /// 
+    /// The created `MultiHistory` instance.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiHistory;
+    /// let multihistory = MultiHistory::new(10, 4);
+    /// ```
+    /// 
+    pub fn new(object : T, capacity : usize) -> MultiHistory<T> {
+        return MultiHistory {
+            value    : MultiRef::new(object),
+            history  : RefCell::new(VecDeque::with_capacity(capacity)),
+            capacity
+        };
+    }
+
+    /// Get an immutable reference to the wrapped value. This is not recorded.
+    /// Can be used simultaneously with `get_mut()`s or other `get_ref()`s.
+    /// 
+    /// # Returns
+    /// 
+    /// An immutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The caller must make sure that the wrapped value is not being written to through another reference or guard
+    /// while the returned reference is in use.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiHistory;
+    /// let multihistory = MultiHistory::new(10, 4);
+    /// 
+    /// assert_eq!(unsafe {*multihistory.get_ref()}, 10);
+    /// ```
+    /// 
+    pub unsafe fn get_ref(&self) -> &T {
+        return self.value.get_ref();
+    }
+
+    /// Get a guard that gives mutable access to the wrapped value. A clone of the value is recorded when the guard is dropped.
+    /// Can be used simultaneously with `get_ref()`s or other `get_mut()`s.
+    /// 
+    /// # Returns
+    /// 
+    /// A guard that derefs mutably to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// References obtained through the guard alias every other reference obtained from this `MultiHistory`.
+    /// The value is cloned when the guard is dropped, so it must not be being written to through another reference at that point.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiHistory;
+    /// let multihistory = MultiHistory::new(10, 4);
+    /// 
+    /// let mut a = unsafe {multihistory.get_mut()};
+    /// let mut b = unsafe {multihistory.get_mut()};
+    /// *a += 1;
+    /// *b += 2;
+    /// drop(a);
+    /// drop(b);
+    /// assert_eq!(multihistory.history(), vec![13, 13]);
+    /// ```
+    /// 
+    pub unsafe fn get_mut(&self) -> MultiHistoryMut<'_, T> {
+        return MultiHistoryMut(self);
+    }
+
+    /// Get the recorded values.
+    /// 
+    /// # Returns
+    /// 
+    /// Clones of the most recent recorded values, oldest first.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiHistory;
+    /// let multihistory = MultiHistory::new(String::from("a"), 4);
+    /// 
+    /// unsafe {multihistory.get_mut()}.push('b');
+    /// unsafe {multihistory.get_mut()}.push('c');
+    /// assert_eq!(multihistory.history(), vec!["ab", "abc"]);
+    /// ```
+    /// 
+    pub fn history(&self) -> Vec<T> {
+        return self.history.borrow().iter().cloned().collect();
+    }
+
+    /// Return the wrapped value and drop the `MultiHistory` and its history.
+    /// 
+    /// # Returns
+    /// 
+    /// The value that was wrapped.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiHistory;
+    /// let multihistory = MultiHistory::new(10, 4);
+    /// 
+    /// assert_eq!(multihistory.unwrap(), 10);
+    /// ```
+    /// 
+    pub fn unwrap(self) -> T {
+        return self.value.unwrap();
+    }
+
+    fn record(&self) {
+        if self.capacity == 0 {
+            return;
+        }
+        let value = unsafe {self.value.get_ref()}.clone();
+        let mut history = self.history.borrow_mut();
+        if history.len() == self.capacity {
+            history.pop_front();
+        }
+        history.push_back(value);
+    }
+
+}
+
+
+/// A guard giving mutable access to the value of a `MultiHistory`, created with `MultiHistory::get_mut()`.
+/// Records a clone of the value in the history when dropped.
+/// 
+/// # Generics
+/// 
+/// * `T` : The type of the wrapped value.
+/// 
+pub struct MultiHistoryMut<'a, T : Clone>(&'a MultiHistory<T>);
+
+impl<T : Clone> Deref for MultiHistoryMut<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        return unsafe {self.0.value.get_ref()};
+    }
+}
+
+impl<T : Clone> DerefMut for MultiHistoryMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        return unsafe {self.0.value.get_mut()};
+    }
+}
+
+impl<T : Clone> Drop for MultiHistoryMut<'_, T> {
+    fn drop(&mut self) {
+        self.0.record();
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn history() {unsafe {
+        let multihistory = MultiRef::new_with_history(vec![0], 3);
+        assert!(multihistory.history().is_empty());
+
+        for i in 1..=5 {
+            multihistory.get_mut().push(i);
+        }
+        assert_eq!(multihistory.history(), vec![
+            vec![0, 1, 2, 3],
+            vec![0, 1, 2, 3, 4],
+            vec![0, 1, 2, 3, 4, 5]
+        ]);
+
+        // Reads are not recorded.
+        assert_eq!(multihistory.get_ref().len(), 6);
+        assert_eq!(multihistory.history().len(), 3);
+        assert_eq!(multihistory.unwrap(), vec![0, 1, 2, 3, 4, 5]);
+
+        let multihistory = MultiHistory::new(0, 0);
+        *multihistory.get_mut() += 1;
+        assert!(multihistory.history().is_empty());
+        assert_eq!(multihistory.unwrap(), 1);
+    }}
+
+}
@@ -0,0 +1,138 @@
+use std::ops::Deref;
+
+use crate::MultiRef;
+
+
+/// A `MultiRef` with a hook that is called with the final value when it is unwrapped, e.g. for teardown accounting.
+/// The hook is not called if the `MultiHooked` is dropped without being unwrapped.
+/// 
+/// Dereferences to the inner `MultiRef`, so all of its accessors can be used directly.
+/// 
+/// # Broken Borrow Checker Rules
+/// 
+/// * [ ] Variables must be initialised before use.
+/// * [ ] Values can not be moved more than once.
+/// * [ ] Values can not be moved while borrowed.
+/// * [x] Values can not be accessed while mutably borrowed.
+/// * [x] Values can not be mutated while immutably borrowed.
+/// 
+/// # Generics
+/// 
+/// * `T` : The type of the wrapped value.
+/// * `H` : The type of the hook.
+/// 
+/// # Warning
+/// 
+/// * This structure is not thread safe.
+/// * You are responsible for preventing undefined behaviour.
+/// * IN MOST CASES THIS SHOULD NOT BE USED DUE TO THE UNPREDICTABLE AND DANGEROUS NATURE OF THIS SYSTEM.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// let mut total = 0;
+/// let hooked = MultiRef::new_with_unwrap_hook(10, |value| total += *value);
+/// 
+/// unsafe {*hooked.get_mut() += 1};
+/// assert_eq!(hooked.unwrap(), 11);
+/// assert_eq!(total, 11);
+/// ```
+/// 
+pub struct MultiHooked<T, H : FnOnce(&T)> {
+    value : MultiRef<T>,
+    hook  : H
+}
+
+impl<T, H : FnOnce(&T)> MultiHooked<T, H> {
+
+    /// Create a new `MultiHooked` instance. The same as `MultiRef::new_with_unwrap_hook()`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `object` : The object to wrap in the created `MultiHooked`.
+    /// * `hook`   : Called with the final value when the `MultiHooked` is unwrapped.
+    /// 
+    /// # Returns
+    /// 
+    /// The created `MultiHooked` instance.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiHooked;
+    /// let hooked = MultiHooked::new(10, |value| println!("unwrapped {}", value));
+    /// ```
+    /// 
+    pub fn new(object : T, hook : H) -> MultiHooked<T, H> {
+        return MultiHooked {value : MultiRef::new(object), hook};
+    }
+
+    /// Call the hook with the wrapped value, then return the value and drop the `MultiHooked`.
+    /// 
+    /// # Returns
+    /// 
+    /// The value that was wrapped.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiHooked;
+    /// let mut seen = None;
+    /// let hooked = MultiHooked::new(10, |value| seen = Some(*value));
+    /// 
+    /// assert_eq!(hooked.unwrap(), 10);
+    /// assert_eq!(seen, Some(10));
+    /// ```
+    /// 
+    pub fn unwrap(self) -> T {
+        let value = self.value.unwrap();
+        (self.hook)(&value);
+        return value;
+    }
+
+}
+
+/// Dereferences to the inner `MultiRef`.
+impl<T, H : FnOnce(&T)> Deref for MultiHooked<T, H> {
+    type Target = MultiRef<T>;
+    fn deref(&self) -> &MultiRef<T> {
+        return &self.value;
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn unwrap_hook() {unsafe {
+        let calls = Cell::new(0);
+        let last = Cell::new(0);
+        let hook = |value : &Vec<i32>| {
+            calls.set(calls.get() + 1);
+            last.set(value.iter().sum());
+        };
+
+        let hooked = MultiRef::new_with_unwrap_hook(vec![1], hook);
+        let a = hooked.get_mut();
+        let b = hooked.get_mut();
+        a.push(2);
+        b.push(3);
+        assert_eq!(calls.get(), 0);
+        assert_eq!(hooked.unwrap(), vec![1, 2, 3]);
+        assert_eq!((calls.get(), last.get()), (1, 6));
+
+        // Dropping without unwrapping does not call the hook.
+        let hooked = MultiHooked::new(vec![10], hook);
+        hooked.get_mut().push(20);
+        drop(hooked);
+        assert_eq!((calls.get(), last.get()), (1, 6));
+    }}
+
+}
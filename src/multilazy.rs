@@ -0,0 +1,264 @@
+use std::cell::{Cell, UnsafeCell};
+use std::mem::{self, MaybeUninit};
+
+
+/// A `MultiRef` whose value is created on first access, like `LazyCell`.
+/// 
+/// The value is created by the first `get_ref()` or `get_mut()`, whichever comes first, and the init function runs exactly once.
+/// If the init function panics, or accesses the `MultiLazy` it is initialising, every later access panics.
+/// 
+/// # Broken Borrow Checker Rules
+/// 
+/// * [ ] Variables must be initialised before use.
+/// * [ ] Values can not be moved more than once.
+/// * [ ] Values can not be moved while borrowed.
+/// * [x] Values can not be accessed while mutably borrowed.
+/// * [x] Values can not be mutated while immutably borrowed.
+/// 
+/// # Generics
+/// 
+/// * `T` : The type of the wrapped value.
+/// * `F` : The type of the init function.
+/// 
+/// # Warning
+/// 
+/// * This structure is not thread safe, the same as `MultiRef`. Use `LazyLock` to share a lazy value between threads.
+/// * You are responsible for preventing undefined behaviour.
+/// * IN MOST CASES THIS SHOULD NOT BE USED DUE TO THE UNPREDICTABLE AND DANGEROUS NATURE OF THIS SYSTEM.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiLazy;
+/// let multilazy = MultiLazy::new(|| vec![1, 2]);
+/// 
+/// let a = unsafe {multilazy.get_mut()};
+/// let b = unsafe {multilazy.get_mut()};
+/// a.push(3);
+/// b.push(4);
+/// assert_eq!(multilazy.unwrap(), vec![1, 2, 3, 4]);
+/// ```
+/// 
+pub struct MultiLazy<T, F = fn() -> T> {
+    value       : UnsafeCell<MaybeUninit<T>>,
+    initialised : Cell<bool>,
+    init        : Cell<Option<F>>
+}
+
+impl<T, F : FnOnce() -> T> MultiLazy<T, F> {
+
+    /// Create a new `MultiLazy` instance. The init function is not called yet.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `init` : The function that creates the wrapped value on first access.
+    /// 
+    /// # Returns
+    /// 
+    /// The created `MultiLazy` instance.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiLazy;
+    /// let multilazy = MultiLazy::new(|| 10);
+    /// ```
+    /// 
+    pub const fn new(init : F) -> MultiLazy<T, F> {
+        return MultiLazy {
+            value       : UnsafeCell::new(MaybeUninit::uninit()),
+            initialised : Cell::new(false),
+            init        : Cell::new(Some(init))
+        };
+    }
+
+    /// Get an immutable reference to the wrapped value, creating it first if needed.
+    /// Can be used simultaneously with `get_mut()`s or other `get_ref()`s.
+    /// 
+    /// # Returns
+    /// 
+    /// An immutable reference to the wrapped value.
+    /// 
+    /// # Panics
+    /// 
+    /// If the init function panics, or panicked during an earlier access.
+    /// 
+    /// # Safety
+    /// 
+    /// The caller must make sure that the wrapped value is not being written to through another reference
+    /// while the returned reference is in use.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiLazy;
+    /// let multilazy = MultiLazy::new(|| 10);
+    /// 
+    /// assert_eq!(unsafe {*multilazy.get_ref()}, 10);
+    /// ```
+    /// 
+    pub unsafe fn get_ref(&self) -> &T {
+        return &*self.force();
+    }
+
+    /// Get a mutable reference to the wrapped value, creating it first if needed.
+    /// Can be used simultaneously with `get_ref()`s or other `get_mut()`s.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable reference to the wrapped value.
+    /// 
+    /// # Panics
+    /// 
+    /// If the init function panics, or panicked during an earlier access.
+    /// 
+    /// # Safety
+    /// 
+    /// The returned reference aliases every other reference obtained from this `MultiLazy`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiLazy;
+    /// let multilazy = MultiLazy::new(|| 10);
+    /// 
+    /// unsafe {*multilazy.get_mut() += 1};
+    /// assert_eq!(unsafe {*multilazy.get_ref()}, 11);
+    /// ```
+    /// 
+    pub unsafe fn get_mut(&self) -> &mut T {
+        return &mut *self.force();
+    }
+
+    /// Return the wrapped value and drop the `MultiLazy`, creating the value first if it was never accessed.
+    /// 
+    /// # Returns
+    /// 
+    /// The value that was wrapped.
+    /// 
+    /// # Panics
+    /// 
+    /// If the init function panics, or panicked during an earlier access.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiLazy;
+    /// let multilazy = MultiLazy::new(|| 10);
+    /// 
+    /// assert_eq!(multilazy.unwrap(), 10);
+    /// ```
+    /// 
+    pub fn unwrap(self) -> T {
+        let value = unsafe {self.force().read()};
+        // The value has been moved out, and the init function is gone, so there is nothing left to drop.
+        mem::forget(self);
+        return value;
+    }
+
+    // Creates the value if this is the first access, and returns a pointer to it.
+    // Only raw pointers are used, so references handed out earlier stay valid.
+    unsafe fn force(&self) -> *mut T {
+        let value = self.value.get() as *mut T;
+        if !self.initialised.get() {
+            let Some(init) = self.init.take() else {
+                panic!("MultiLazy was accessed during or after a failed initialisation");
+            };
+            value.write(init());
+            self.initialised.set(true);
+        }
+        return value;
+    }
+
+}
+
+/// Drops the wrapped value, if it was ever created.
+impl<T, F> Drop for MultiLazy<T, F> {
+    fn drop(&mut self) {
+        if self.initialised.get() {
+            unsafe {self.value.get_mut().assume_init_drop()};
+        }
+    }
+}
+
+/// Create a `MultiLazy` that initialises to `T::default()`.
+impl<T : Default> Default for MultiLazy<T> {
+    fn default() -> MultiLazy<T> {
+        return MultiLazy::new(T::default);
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    #[test]
+    fn init_once() {unsafe {
+        let calls = Cell::new(0);
+        let multilazy = MultiLazy::new(|| {
+            calls.set(calls.get() + 1);
+            return vec![1];
+        });
+        assert_eq!(calls.get(), 0);
+
+        // The first access is mutable.
+        let a = multilazy.get_mut();
+        let b = multilazy.get_mut();
+        a.push(2);
+        b.push(3);
+
+        // Reads in a row do not invalidate each other.
+        let first = multilazy.get_ref();
+        let second = multilazy.get_ref();
+        assert_eq!(*first, vec![1, 2, 3]);
+        assert_eq!(*second, vec![1, 2, 3]);
+        assert_eq!(*first, *multilazy.get_ref());
+        assert_eq!(multilazy.unwrap(), vec![1, 2, 3]);
+        assert_eq!(calls.get(), 1);
+
+        let multilazy = MultiLazy::<String>::default();
+        multilazy.get_mut().push('a');
+        assert_eq!(multilazy.unwrap(), "a");
+    }}
+
+    #[test]
+    fn poisoned() {unsafe {
+        let multilazy = MultiLazy::new(|| -> i32 {panic!("init failed")});
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| *multilazy.get_mut() += 1)).is_err());
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| *multilazy.get_ref())).is_err());
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| multilazy.unwrap())).is_err());
+    }}
+
+    #[test]
+    fn drop_value() {unsafe {
+        let value = Rc::new(());
+
+        // Never accessed, so the value is never created and the init function is dropped unused.
+        let captured = Rc::clone(&value);
+        let multilazy = MultiLazy::new(move || captured);
+        assert_eq!(Rc::strong_count(&value), 2);
+        drop(multilazy);
+        assert_eq!(Rc::strong_count(&value), 1);
+
+        // Accessed, so dropping the `MultiLazy` drops the created value.
+        let captured = Rc::clone(&value);
+        let multilazy = MultiLazy::new(move || captured);
+        let _ = multilazy.get_ref();
+        assert_eq!(Rc::strong_count(&value), 2);
+        drop(multilazy);
+        assert_eq!(Rc::strong_count(&value), 1);
+
+        let captured = Rc::clone(&value);
+        let unwrapped = MultiLazy::new(move || captured).unwrap();
+        assert_eq!(Rc::strong_count(&value), 2);
+        drop(unwrapped);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }}
+
+}
@@ -0,0 +1,315 @@
+use std::borrow::Borrow;
+use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+
+/// A `HashMap` whose values can have multiple mutable references at once, including several to the same key.
+/// This is `HashMap::get_disjoint_mut` without the requirement that the keys are distinct.
+/// 
+/// # Broken Borrow Checker Rules
+/// 
+/// * [ ] Variables must be initialised before use.
+/// * [ ] Values can not be moved more than once.
+/// * [ ] Values can not be moved while borrowed.
+/// * [x] Values can not be accessed while mutably borrowed.
+/// * [x] Values can not be mutated while immutably borrowed.
+/// 
+/// # Generics
+/// 
+/// * `K` : The type of the keys.
+/// * `V` : The type of the values.
+/// 
+/// # Warning
+/// 
+/// * This structure is not thread safe.
+/// * You are responsible for preventing undefined behaviour.
+/// * IN MOST CASES THIS SHOULD NOT BE USED DUE TO THE UNPREDICTABLE AND DANGEROUS NATURE OF THIS SYSTEM.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiMap;
+/// let mut multimap = MultiMap::new();
+/// multimap.insert("a", 1);
+/// multimap.insert("b", 2);
+/// 
+/// let [a, b] = unsafe {multimap.get_many_mut([&"a", &"b"])};
+/// *a.unwrap() += *b.unwrap();
+/// assert_eq!(multimap.into_map()["a"], 3);
+/// ```
+/// 
+pub struct MultiMap<K, V>(HashMap<K, UnsafeCell<V>>);
+
+impl<K : Hash + Eq, V> MultiMap<K, V> {
+
+    /// Create a new, empty `MultiMap` instance.
+    /// 
+    /// # Returns
+    /// 
+    /// The created `MultiMap` instance.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiMap;
+    /// let multimap = MultiMap::<&str, i32>::new();
+    /// ```
+    /// 
+    pub fn new() -> MultiMap<K, V> {
+        return MultiMap(HashMap::new());
+    }
+
+    /// Get the number of entries.
+    /// 
+    /// # Returns
+    /// 
+    /// The number of entries in the map.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiMap;
+    /// let mut multimap = MultiMap::new();
+    /// multimap.insert("a", 1);
+    /// 
+    /// assert_eq!(multimap.len(), 1);
+    /// ```
+    /// 
+    pub fn len(&self) -> usize {
+        return self.0.len();
+    }
+
+    /// Check whether there are no entries.
+    /// 
+    /// # Returns
+    /// 
+    /// `true` if the map is empty.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiMap;
+    /// let multimap = MultiMap::<&str, i32>::new();
+    /// 
+    /// assert!(multimap.is_empty());
+    /// ```
+    /// 
+    pub fn is_empty(&self) -> bool {
+        return self.0.is_empty();
+    }
+
+    /// Insert an entry. This takes `&mut self`, so it can not invalidate any value references that are still alive.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `key`   : The key of the entry.
+    /// * `value` : The value of the entry.
+    /// 
+    /// # Returns
+    /// 
+    /// The previous value for `key`, if there was one.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiMap;
+    /// let mut multimap = MultiMap::new();
+    /// 
+    /// assert_eq!(multimap.insert("a", 1), None);
+    /// assert_eq!(multimap.insert("a", 2), Some(1));
+    /// ```
+    /// 
+    pub fn insert(&mut self, key : K, value : V) -> Option<V> {
+        return self.0.insert(key, UnsafeCell::new(value)).map(UnsafeCell::into_inner);
+    }
+
+    /// Remove an entry. This takes `&mut self`, so it can not invalidate any value references that are still alive.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `key` : The key of the entry.
+    /// 
+    /// # Returns
+    /// 
+    /// The removed value, if there was one.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiMap;
+    /// let mut multimap = MultiMap::new();
+    /// multimap.insert("a", 1);
+    /// 
+    /// assert_eq!(multimap.remove("a"), Some(1));
+    /// assert!(multimap.is_empty());
+    /// ```
+    /// 
+    pub fn remove<Q : Hash + Eq + ?Sized>(&mut self, key : &Q) -> Option<V> where K : Borrow<Q> {
+        return self.0.remove(key).map(UnsafeCell::into_inner);
+    }
+
+    /// Get an immutable reference to the value for a key.
+    /// Can be used simultaneously with `get_mut()`s or other `get_ref()`s.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `key` : The key of the entry.
+    /// 
+    /// # Returns
+    /// 
+    /// An immutable reference to the value, or `None` if there is no entry for `key`.
+    /// 
+    /// # Safety
+    /// 
+    /// The caller must make sure that the value is not being written to through another reference
+    /// while the returned reference is in use.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiMap;
+    /// let mut multimap = MultiMap::new();
+    /// multimap.insert("a", 1);
+    /// 
+    /// assert_eq!(unsafe {multimap.get_ref("a")}, Some(&1));
+    /// assert_eq!(unsafe {multimap.get_ref("b")}, None);
+    /// ```
+    /// 
+    pub unsafe fn get_ref<Q : Hash + Eq + ?Sized>(&self, key : &Q) -> Option<&V> where K : Borrow<Q> {
+        return self.0.get(key).map(|value| &*value.get());
+    }
+
+    /// Get a mutable reference to the value for a key.
+    /// Can be used simultaneously with `get_ref()`s or other `get_mut()`s.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `key` : The key of the entry.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable reference to the value, or `None` if there is no entry for `key`.
+    /// 
+    /// # Safety
+    /// 
+    /// The returned reference aliases every other reference to the same value obtained from this `MultiMap`.
+    /// References to other values are not affected.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiMap;
+    /// let mut multimap = MultiMap::new();
+    /// multimap.insert("a", 1);
+    /// 
+    /// *unsafe {multimap.get_mut("a")}.unwrap() += 1;
+    /// assert_eq!(unsafe {multimap.get_ref("a")}, Some(&2));
+    /// ```
+    /// 
+    pub unsafe fn get_mut<Q : Hash + Eq + ?Sized>(&self, key : &Q) -> Option<&mut V> where K : Borrow<Q> {
+        return self.0.get(key).map(|value| &mut *value.get());
+    }
+
+    /// Get mutable references to the values for several keys at once. The keys do not have to be distinct.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `keys` : The keys of the entries.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable reference to the value for each key, in the same order, or `None` where there is no entry.
+    /// 
+    /// # Safety
+    /// 
+    /// The same as `get_mut()`. If a key is given more than once, the references for it point to the same value.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiMap;
+    /// let mut multimap = MultiMap::new();
+    /// multimap.insert("a", 1);
+    /// 
+    /// let [x, y, z] = unsafe {multimap.get_many_mut([&"a", &"a", &"b"])};
+    /// *x.unwrap() += 1;
+    /// *y.unwrap() += 1;
+    /// assert!(z.is_none());
+    /// assert_eq!(unsafe {multimap.get_ref("a")}, Some(&3));
+    /// ```
+    /// 
+    pub unsafe fn get_many_mut<Q : Hash + Eq + ?Sized, const N : usize>(&self, keys : [&Q; N]) -> [Option<&mut V>; N] where K : Borrow<Q> {
+        return keys.map(|key| self.get_mut(key));
+    }
+
+    /// Return the entries and drop the `MultiMap`.
+    /// 
+    /// # Returns
+    /// 
+    /// A `HashMap` holding the entries.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiMap;
+    /// let mut multimap = MultiMap::new();
+    /// multimap.insert("a", 1);
+    /// 
+    /// assert_eq!(multimap.into_map()["a"], 1);
+    /// ```
+    /// 
+    pub fn into_map(self) -> HashMap<K, V> {
+        return self.0.into_iter().map(|(key, value)| (key, value.into_inner())).collect();
+    }
+
+}
+
+/// Create an empty `MultiMap`, the same as `new()`.
+impl<K : Hash + Eq, V> Default for MultiMap<K, V> {
+    fn default() -> MultiMap<K, V> {
+        return MultiMap::new();
+    }
+}
+
+/// Collect key-value pairs into a `MultiMap`.
+impl<K : Hash + Eq, V> FromIterator<(K, V)> for MultiMap<K, V> {
+    fn from_iter<I : IntoIterator<Item = (K, V)>>(iter : I) -> MultiMap<K, V> {
+        return MultiMap(iter.into_iter().map(|(key, value)| (key, UnsafeCell::new(value))).collect());
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn get_many_mut() {unsafe {
+        let accounts = [(String::from("alice"), 100), (String::from("bob"), 50)].into_iter().collect::<MultiMap<_, _>>();
+        // Two distinct keys.
+        let [from, to] = accounts.get_many_mut(["alice", "bob"]);
+        let (from, to) = (from.unwrap(), to.unwrap());
+        *from -= 30;
+        *to += 30;
+        assert_eq!(accounts.get_ref("alice"), Some(&70));
+        assert_eq!(accounts.get_ref("bob"), Some(&80));
+
+        // The same key twice.
+        let [a, b, missing] = accounts.get_many_mut(["alice", "alice", "carol"]);
+        let (a, b) = (a.unwrap(), b.unwrap());
+        *a += 1;
+        *b += 1;
+        assert_eq!(accounts.get_ref("alice"), Some(&72));
+        assert!(missing.is_none());
+
+        let mut accounts = accounts;
+        assert_eq!(accounts.remove("bob"), Some(80));
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts.into_map(), HashMap::from([(String::from("alice"), 72)]));
+    }}
+
+}
@@ -0,0 +1,290 @@
+use std::cell::UnsafeCell;
+use std::pin::Pin;
+
+
+/// A `MultiRef` whose value is pinned on the heap, for building self-referential structures.
+/// 
+/// # Broken Borrow Checker Rules
+/// 
+/// * [ ] Variables must be initialised before use.
+/// * [ ] Values can not be moved more than once.
+/// * [ ] Values can not be moved while borrowed.
+/// * [x] Values can not be accessed while mutably borrowed.
+/// * [x] Values can not be mutated while immutably borrowed.
+/// 
+/// # Generics
+/// 
+/// * `T` : The type of the wrapped value.
+/// 
+/// # Address Stability
+/// 
+/// The value lives in its own heap allocation that is only freed when the `MultiPin` is dropped.
+/// Moving the `MultiPin` moves the handle, not the value, so `as_ptr()` and `as_mut_ptr()` return the same address for its whole life.
+/// Pointers from the value into itself stay valid as long as nothing moves the value out through `get_mut()`
+/// (e.g. with `mem::swap` or `mem::replace`).
+/// 
+/// # Warning
+/// 
+/// * This structure is not thread safe.
+/// * You are responsible for preventing undefined behaviour.
+/// * IN MOST CASES THIS SHOULD NOT BE USED DUE TO THE UNPREDICTABLE AND DANGEROUS NATURE OF THIS SYSTEM.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiPin;
+/// let multipin = MultiPin::new(10);
+/// let address = multipin.as_ptr();
+/// 
+/// let moved = multipin;
+/// assert_eq!(moved.as_ptr(), address);
+/// ```
+/// 
+pub struct MultiPin<T>(Pin<Box<UnsafeCell<T>>>);
+
+impl<T> MultiPin<T> {
+
+    /// Create a new `MultiPin` instance, moving the value onto the heap.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `object` : The object to wrap in the created `MultiPin`.
+    /// 
+    /// # Returns
+    /// 
+    /// The created `MultiPin` instance.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiPin;
+    /// let multipin = MultiPin::new(10);
+    /// ```
+    /// 
+    pub fn new(object : T) -> MultiPin<T> {
+        return MultiPin(Box::pin(UnsafeCell::new(object)));
+    }
+
+    /// Get an immutable reference to the wrapped value.
+    /// Can be used simultaneously with `get_mut()`s or other `get_ref()`s.
+    /// 
+    /// # Returns
+    /// 
+    /// An immutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The caller must make sure that the wrapped value is not being written to through another reference
+    /// while the returned reference is in use.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiPin;
+    /// let multipin = MultiPin::new(10);
+    /// 
+    /// let i = unsafe {multipin.get_ref()};
+    /// assert_eq!(*i, 10);
+    /// ```
+    /// 
+    pub unsafe fn get_ref(&self) -> &T {
+        return &*self.0.get();
+    }
+
+    /// Get a mutable reference to the wrapped value.
+    /// Can be used simultaneously with `get_ref()`s or other `get_mut()`s.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The returned reference aliases every other reference obtained from this `MultiPin`.
+    /// The value must not be moved out through it (e.g. with `mem::swap`), or pointers into it are left dangling.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiPin;
+    /// let multipin = MultiPin::new(10);
+    /// 
+    /// unsafe {*multipin.get_mut() += 1};
+    /// assert_eq!(unsafe {*multipin.get_ref()}, 11);
+    /// ```
+    /// 
+    pub unsafe fn get_mut(&self) -> &mut T {
+        return &mut *self.0.get();
+    }
+
+    /// Get a pinned mutable reference to the wrapped value, for APIs that take `Pin<&mut T>` (e.g. `Future::poll`).
+    /// 
+    /// # Returns
+    /// 
+    /// A pinned mutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The same as `get_mut()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiPin;
+    /// use std::future::Future;
+    /// use std::task::{Context, Poll, Waker};
+    /// let multipin = MultiPin::new(async {10});
+    /// 
+    /// let mut context = Context::from_waker(Waker::noop());
+    /// let poll = unsafe {multipin.as_pin_mut()}.poll(&mut context);
+    /// assert_eq!(poll, Poll::Ready(10));
+    /// ```
+    /// 
+    pub unsafe fn as_pin_mut(&self) -> Pin<&mut T> {
+        return Pin::new_unchecked(self.get_mut());
+    }
+
+    /// Get a raw pointer to the wrapped value, which stays the same for the life of the `MultiPin`.
+    /// 
+    /// # Returns
+    /// 
+    /// A raw pointer to the wrapped value. Dereferencing it is up to the caller.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiPin;
+    /// let multipin = MultiPin::new(10);
+    /// 
+    /// assert_eq!(unsafe {*multipin.as_ptr()}, 10);
+    /// ```
+    /// 
+    pub fn as_ptr(&self) -> *const T {
+        return self.0.get();
+    }
+
+    /// Get a mutable raw pointer to the wrapped value, which stays the same for the life of the `MultiPin`.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable raw pointer to the wrapped value. Dereferencing it is up to the caller.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiPin;
+    /// let multipin = MultiPin::new(10);
+    /// 
+    /// unsafe {*multipin.as_mut_ptr() += 1};
+    /// assert_eq!(unsafe {*multipin.get_ref()}, 11);
+    /// ```
+    /// 
+    pub fn as_mut_ptr(&self) -> *mut T {
+        return self.0.get();
+    }
+
+    /// Turn the `MultiPin` into a plain pinned box, without moving the value.
+    /// Works for `!Unpin` values, and pointers from the value into itself stay valid.
+    /// 
+    /// # Returns
+    /// 
+    /// A pinned box holding the wrapped value in the same allocation.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiPin;
+    /// let multipin = MultiPin::new(async {10});
+    /// let address = multipin.as_ptr();
+    /// 
+    /// let pinned = multipin.into_pin();
+    /// assert_eq!(&*pinned as *const _, address);
+    /// ```
+    /// 
+    pub fn into_pin(self) -> Pin<Box<T>> {
+        let boxed = unsafe {Pin::into_inner_unchecked(self.0)};
+        // `UnsafeCell<T>` has the same layout as `T`, so the allocation carries over, and the value is not moved.
+        return unsafe {Pin::new_unchecked(Box::from_raw(Box::into_raw(boxed) as *mut T))};
+    }
+
+    /// Return the wrapped value and drop the `MultiPin`, moving the value off the heap.
+    /// Only available for `Unpin` values, since moving a pinned `!Unpin` value is not allowed. Use `into_pin()` for those.
+    /// 
+    /// # Returns
+    /// 
+    /// The value that was wrapped.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiPin;
+    /// let multipin = MultiPin::new(10);
+    /// 
+    /// unsafe {*multipin.get_mut() += 1};
+    /// assert_eq!(multipin.unwrap(), 11);
+    /// ```
+    /// 
+    pub fn unwrap(self) -> T where T : Unpin {
+        return *Pin::into_inner(self.into_pin());
+    }
+
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::marker::PhantomPinned;
+    use std::ptr;
+
+    struct Node {
+        pub name     : String,
+        pub name_ptr : *const String,
+        pub next     : *const Node,
+        _pinned      : PhantomPinned
+    }
+
+    #[test]
+    fn self_referential() {unsafe {
+        let node = MultiPin::new(Node {
+            name     : String::from("a"),
+            name_ptr : ptr::null(),
+            next     : ptr::null(),
+            _pinned  : PhantomPinned
+        });
+        node.get_mut().name_ptr = &node.get_ref().name;
+        node.get_mut().next = node.as_ptr();
+
+        node.get_mut().name.push_str("bc");
+        assert_eq!(*node.get_ref().name_ptr, "abc");
+
+        let moved = node;
+        moved.get_mut().name.push('d');
+        assert_eq!(*moved.get_ref().name_ptr, "abcd");
+        assert_eq!((*moved.get_ref().next).name, "abcd");
+        assert!(ptr::eq(moved.get_ref().next, moved.as_ptr()));
+
+        let pinned = moved.as_pin_mut();
+        assert_eq!(pinned.name, "abcd");
+
+        // Recovering the value keeps it in place, so its pointers into itself stay valid.
+        let address = moved.as_ptr();
+        let boxed = moved.into_pin();
+        assert!(ptr::eq(&*boxed, address));
+        assert_eq!(*boxed.name_ptr, "abcd");
+        assert!(ptr::eq(boxed.next, address));
+    }}
+
+    #[test]
+    fn unwrap() {unsafe {
+        let multipin = MultiPin::new(vec![1]);
+        let reader = multipin.get_ref();
+        assert_eq!(*reader, vec![1]);
+        multipin.get_mut().push(2);
+        assert_eq!(multipin.unwrap(), vec![1, 2]);
+    }}
+
+}
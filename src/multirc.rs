@@ -0,0 +1,327 @@
+use std::cell::UnsafeCell;
+use std::rc::{Rc, Weak};
+
+
+/// A reference counted `MultiRef`. Cloning it creates another handle to the same value, not a copy.
+/// 
+/// # Broken Borrow Checker Rules
+/// 
+/// * [ ] Variables must be initialised before use.
+/// * [ ] Values can not be moved more than once.
+/// * [ ] Values can not be moved while borrowed.
+/// * [x] Values can not be accessed while mutably borrowed.
+/// * [x] Values can not be mutated while immutably borrowed.
+/// 
+/// # Generics
+/// 
+/// * `T` : The type of the wrapped value.
+/// 
+/// # Warning
+/// 
+/// * This structure is not thread safe.
+/// * You are responsible for preventing undefined behaviour.
+/// * IN MOST CASES THIS SHOULD NOT BE USED DUE TO THE UNPREDICTABLE AND DANGEROUS NATURE OF THIS SYSTEM.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRc;
+/// let a = MultiRc::new(10);
+/// let b = a.clone();
+/// 
+/// unsafe {*b.get_mut() += 1};
+/// assert_eq!(unsafe {*a.get_ref()}, 11);
+/// ```
+/// 
+pub struct MultiRc<T>(Rc<UnsafeCell<T>>);
+
+impl<T> MultiRc<T> {
+
+    /// Create a new `MultiRc` instance.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `object` : The object to wrap in the created `MultiRc`.
+    /// 
+    /// # Returns
+    /// 
+    /// The created `MultiRc` instance, the only handle to its value.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRc;
+    /// let multirc = MultiRc::new(10);
+    /// ```
+    /// 
+    pub fn new(object : T) -> MultiRc<T> {
+        return MultiRc(Rc::new(UnsafeCell::new(object)));
+    }
+
+    /// Get an immutable reference to the wrapped value.
+    /// Can be used simultaneously with `get_mut()`s or other `get_ref()`s, from this handle or any clone.
+    /// 
+    /// # Returns
+    /// 
+    /// An immutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The caller must make sure that the wrapped value is not being written to through another reference
+    /// while the returned reference is in use.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRc;
+    /// let multirc = MultiRc::new(10);
+    /// 
+    /// let i = unsafe {multirc.get_ref()};
+    /// assert_eq!(*i, 10);
+    /// ```
+    /// 
+    pub unsafe fn get_ref(&self) -> &T {
+        return &*self.0.get();
+    }
+
+    /// Get a mutable reference to the wrapped value.
+    /// Can be used simultaneously with `get_ref()`s or other `get_mut()`s, from this handle or any clone.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The returned reference aliases every other reference obtained from this handle and its clones.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRc;
+    /// let a = MultiRc::new(10);
+    /// let b = a.clone();
+    /// 
+    /// let x = unsafe {a.get_mut()};
+    /// let y = unsafe {b.get_mut()};
+    /// *x += 1;
+    /// *y += 2;
+    /// assert_eq!(unsafe {*a.get_ref()}, 13);
+    /// ```
+    /// 
+    pub unsafe fn get_mut(&self) -> &mut T {
+        return &mut *self.0.get();
+    }
+
+    /// Return the wrapped value if this is the only handle to it, like `Rc::try_unwrap`.
+    /// 
+    /// # Returns
+    /// 
+    /// `Ok` with the wrapped value, or `Err` with this handle if there are other clones alive.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRc;
+    /// let a = MultiRc::new(10);
+    /// let b = a.clone();
+    /// 
+    /// let a = a.try_unwrap().unwrap_err();
+    /// drop(b);
+    /// assert_eq!(a.try_unwrap().ok(), Some(10));
+    /// ```
+    /// 
+    pub fn try_unwrap(self) -> Result<T, MultiRc<T>> {
+        return Rc::try_unwrap(self.0)
+            .map(UnsafeCell::into_inner)
+            .map_err(MultiRc);
+    }
+
+    /// Create a weak handle to the wrapped value, which does not keep it alive.
+    /// Useful for breaking reference cycles, e.g. child nodes pointing back to their parent.
+    /// 
+    /// # Returns
+    /// 
+    /// A `MultiWeak` pointing to the same value.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRc;
+    /// let multirc = MultiRc::new(10);
+    /// 
+    /// let weak = multirc.downgrade();
+    /// assert!(weak.upgrade().is_some());
+    /// drop(multirc);
+    /// assert!(weak.upgrade().is_none());
+    /// ```
+    /// 
+    pub fn downgrade(&self) -> MultiWeak<T> {
+        return MultiWeak(Rc::downgrade(&self.0));
+    }
+
+}
+
+/// Create another handle to the same value. The value itself is not cloned.
+impl<T> Clone for MultiRc<T> {
+    fn clone(&self) -> MultiRc<T> {
+        return MultiRc(Rc::clone(&self.0));
+    }
+}
+
+
+/// A weak handle to the value of a `MultiRc`, created with `MultiRc::downgrade()`, modelled on `std::rc::Weak`.
+/// It does not keep the value alive, and must be upgraded back into a `MultiRc` to access it.
+/// 
+/// # Generics
+/// 
+/// * `T` : The type of the wrapped value.
+/// 
+pub struct MultiWeak<T>(Weak<UnsafeCell<T>>);
+
+impl<T> MultiWeak<T> {
+
+    /// Create a weak handle that points to nothing. Upgrading it always returns `None`.
+    /// 
+    /// # Returns
+    /// 
+    /// The created `MultiWeak`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiWeak;
+    /// let weak = MultiWeak::<i32>::new();
+    /// 
+    /// assert!(weak.upgrade().is_none());
+    /// ```
+    /// 
+    pub fn new() -> MultiWeak<T> {
+        return MultiWeak(Weak::new());
+    }
+
+    /// Get a strong handle to the value, if it is still alive.
+    /// 
+    /// # Returns
+    /// 
+    /// A `MultiRc` to the value, or `None` if every `MultiRc` to it has been dropped.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRc;
+    /// let multirc = MultiRc::new(10);
+    /// 
+    /// let weak = multirc.downgrade();
+    /// unsafe {*weak.upgrade().unwrap().get_mut() += 1};
+    /// assert_eq!(multirc.try_unwrap().ok(), Some(11));
+    /// ```
+    /// 
+    pub fn upgrade(&self) -> Option<MultiRc<T>> {
+        return self.0.upgrade().map(MultiRc);
+    }
+
+}
+
+/// Create another weak handle to the same value.
+impl<T> Clone for MultiWeak<T> {
+    fn clone(&self) -> MultiWeak<T> {
+        return MultiWeak(Weak::clone(&self.0));
+    }
+}
+
+/// Create a weak handle that points to nothing, the same as `new()`.
+impl<T> Default for MultiWeak<T> {
+    fn default() -> MultiWeak<T> {
+        return MultiWeak::new();
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+
+    struct Node {
+        pub value : i32,
+        pub edges : Vec<MultiRc<Node>>
+    }
+
+    struct TreeNode<'l> {
+        pub parent   : MultiWeak<TreeNode<'l>>,
+        pub children : Vec<MultiRc<TreeNode<'l>>>,
+        pub drops    : &'l Cell<u32>
+    }
+
+    impl Drop for TreeNode<'_> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    #[test]
+    fn shared() {unsafe {
+        let a = MultiRc::new(vec![1]);
+        let b = a.clone();
+
+        a.get_mut().push(2);
+        assert_eq!(*a.get_ref(), vec![1, 2]);
+        b.get_mut().push(3);
+        assert_eq!(*a.get_ref(), vec![1, 2, 3]);
+        assert_eq!(*b.get_ref(), vec![1, 2, 3]);
+
+        let a = a.try_unwrap().unwrap_err();
+        let b = b.try_unwrap().unwrap_err();
+        drop(a);
+        assert_eq!(b.try_unwrap().ok(), Some(vec![1, 2, 3]));
+    }}
+
+    #[test]
+    fn graph() {unsafe {
+        let root = MultiRc::new(Node {value : 1, edges : Vec::new()});
+        let leaf = MultiRc::new(Node {value : 2, edges : Vec::new()});
+        root.get_mut().edges.push(leaf.clone());
+        root.get_mut().edges.push(leaf.clone());
+
+        for edge in &root.get_ref().edges {
+            edge.get_mut().value *= 10;
+        }
+        assert_eq!(leaf.get_ref().value, 200);
+
+        let Err(leaf) = leaf.try_unwrap() else {panic!("the root still holds clones")};
+        drop(root);
+        assert_eq!(leaf.try_unwrap().ok().map(|node| node.value), Some(200));
+    }}
+
+
+    #[test]
+    fn weak() {unsafe {
+        let drops = Cell::new(0);
+        let root = MultiRc::new(TreeNode {parent : MultiWeak::new(), children : Vec::new(), drops : &drops});
+        let child = MultiRc::new(TreeNode {parent : root.downgrade(), children : Vec::new(), drops : &drops});
+        root.get_mut().children.push(child.clone());
+
+        let parent = child.get_ref().parent.upgrade().unwrap();
+        assert_eq!(parent.get_ref().children.len(), 1);
+        parent.get_mut().children.clear();
+        assert_eq!(root.get_ref().children.len(), 0);
+        drop(parent);
+
+        // The child only holds a weak handle, so the cycle does not keep the root alive.
+        root.get_mut().children.push(child.clone());
+        let weak_root = child.get_ref().parent.clone();
+        drop(root);
+        assert_eq!(drops.get(), 1);
+        assert!(weak_root.upgrade().is_none());
+        assert!(child.get_ref().parent.upgrade().is_none());
+
+        drop(child);
+        assert_eq!(drops.get(), 2);
+        assert!(MultiWeak::<i32>::default().upgrade().is_none());
+    }}
+
+}
@@ -1,4 +1,23 @@
-use std::cell::UnsafeCell;
+use std::borrow::{Borrow, BorrowMut, Cow};
+use std::cell::{Cell, UnsafeCell};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::future::IntoFuture;
+#[cfg(feature = "hash")]
+use std::hash::{Hash, Hasher};
+use std::mem::{self, ManuallyDrop, MaybeUninit};
+use std::num::NonZeroUsize;
+use std::ops::{Add, AddAssign, SubAssign, MulAssign, DivAssign, BitOrAssign, BitAndAssign, BitXorAssign, ShlAssign, ShrAssign};
+use std::ops::{Deref, DerefMut, Index, IndexMut};
+use std::pin::Pin;
+use std::ptr;
+use std::slice;
+use std::thread;
+
+use crate::{MultiBatched, MultiHooked, Zeroable};
+#[cfg(feature = "history")]
+use crate::MultiHistory;
 
 
 /// A container that can have multiple immutable or mutable references to the wrapped value.
@@ -21,6 +40,48 @@ use std::cell::UnsafeCell;
 /// * You are responsible for preventing data races and undefined behaviour.
 /// * IN MOST CASES THIS SHOULD NOT BE USED DUE TO THE UNPREDICTABLE AND DANGEROUS NATURE OF THIS SYSTEM.
 /// 
+/// # Layout
+/// 
+/// `MultiRef<T>` is `repr(transparent)` over `UnsafeCell<T>`, and so has the same size, alignment and ABI as `T`.
+/// This is a stable guarantee: a `*mut T` can be cast to a `*const MultiRef<T>` and back.
+/// `from_mut()` and `map_ref()` rely on it to view existing data as a `MultiRef` without moving it.
+/// 
+/// `T` may be unsized, so a `MultiRef<[T]>` can be borrowed from a `&MultiRef<[T; N]>` by unsizing coercion,
+/// and split into a cell per element with `as_slice_of_refs()`.
+/// 
+/// # Statics
+/// 
+/// `new()` is a `const fn`, but `MultiRef` is not `Sync`, so a `static` needs a wrapper that promises it is only used from one thread.
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// struct SingleThreaded<T>(T);
+/// unsafe impl<T> Sync for SingleThreaded<T> {}
+/// 
+/// static SCORE : SingleThreaded<MultiRef<u64>> = SingleThreaded(MultiRef::new(0));
+/// 
+/// unsafe {*SCORE.0.get_mut() += 10};
+/// assert_eq!(*SCORE.0, 10);
+/// ```
+/// 
+/// # Pinning
+/// 
+/// A `MultiRef` stores its value inline, so the value moves whenever the `MultiRef` does.
+/// To hold a `!Unpin` value such as a future, pin the `MultiRef` itself with `pinned()` and then use `get_pin_mut()`.
+/// Pinning is structural: once a `Pin<&mut T>` has been handed out, the value must stay where it is until it is dropped.
+/// `unwrap()` needs the `MultiRef` by value, which `Pin<Box<MultiRef<T>>>` does not give out for a `!Unpin` `T`.
+/// The `&self` methods that move the value out (`replace()`, `set()`, `take()`, `swap()`, `replace_with()`, ...)
+/// are already `unsafe`, and must not be used on a pinned `!Unpin` value.
+/// 
+/// ```compile_fail
+/// use pholib::MultiRef;
+/// use std::pin::Pin;
+/// let pinned = MultiRef::new(async {10}).pinned();
+/// 
+/// // A pinned future can not be moved back out of the `MultiRef`.
+/// let future = Pin::into_inner(pinned).unwrap();
+/// ```
+/// 
 /// # Examples
 /// 
 /// ```
@@ -70,12 +131,13 @@ use std::cell::UnsafeCell;
 /// assert_eq!(unwrapped.b, true);
 /// ```
 /// 
-pub struct MultiRef<T>(UnsafeCell<T>);
+#[repr(transparent)]
+pub struct MultiRef<T : ?Sized>(UnsafeCell<T>);
 
-impl<'l, T> MultiRef<T> {
+impl<T> MultiRef<T> {
 
     /// Create a new `MultiRef` instance.
-    /// Because of the unsafe nature of this structure, the `new` function must be wrapped in `unsafe`.
+    /// This is a `const fn`, so it can initialise `static`s (see the type-level docs for the `Sync` wrapper that needs).
     /// 
     /// # Arguments
     /// 
@@ -92,96 +154,95 @@ impl<'l, T> MultiRef<T> {
     /// let multiref = MultiRef::new(10);
     /// ```
     /// 
-    pub fn new(object : T) -> MultiRef<T> {
-        return MultiRef(object.into())
+    pub const fn new(object : T) -> MultiRef<T> {
+        return MultiRef(UnsafeCell::new(object));
     }
 
-    /// Get an immutable reference to the wrapped value.
-    /// Can be used simultaneously with `get_mut()`s or other `get_ref()`s.
-    ///
+    /// Create a `MultiHooked` whose hook is called with the final value when it is unwrapped, but not when it is dropped.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `object` : The object to wrap.
+    /// * `hook`   : Called with the final value right before `unwrap()` returns it.
+    /// 
     /// # Returns
     /// 
-    /// An immutable reference to the wrapped value.
+    /// The created `MultiHooked` instance.
     /// 
     /// # Examples
-    ///
-    /// Basic
-    /// ```
-    /// use pholib::MultiRef;
-    /// let multiref = MultiRef::new(10);
     /// 
-    /// let i = unsafe {multiref.get_ref()};
-    /// assert_eq!(*i, 10);
-    /// ```
-    ///
-    /// Multiple immutable references.
     /// ```
     /// use pholib::MultiRef;
-    /// let multiref = MultiRef::new(10);
+    /// let mut unwrapped = 0;
+    /// let hooked = MultiRef::new_with_unwrap_hook(10, |_| unwrapped += 1);
     /// 
-    /// let a = unsafe {multiref.get_ref()};
-    /// let b = unsafe {multiref.get_ref()};
-    /// assert_eq!(*a, 10);
-    /// assert_eq!(*b, 10);
+    /// hooked.unwrap();
+    /// assert_eq!(unwrapped, 1);
     /// ```
     /// 
-    pub unsafe fn get_ref(&self) -> &T {
-        return & *(&self.0).get();
+    pub fn new_with_unwrap_hook<H : FnOnce(&T)>(object : T, hook : H) -> MultiHooked<T, H> {
+        return MultiHooked::new(object, hook);
     }
 
-    /// Get a mutable reference to the wrapped value.
-    /// Can be used simultaneously with `get_ref()`s or other `get_mut()`s.
-    ///
+    /// Create a `MultiHistory` that records a clone of the value after each mutation made through its `get_mut()`.
+    /// Only available with the `history` feature.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `object`   : The object to wrap.
+    /// * `capacity` : The number of recent values to keep.
+    /// 
     /// # Returns
     /// 
-    /// A mutable reference to the wrapped value.
+    /// The created `MultiHistory` instance.
     /// 
     /// # Examples
-    ///
-    /// Basic
+    /// 
     /// ```
     /// use pholib::MultiRef;
-    /// let multiref = MultiRef::new(10);
+    /// let multihistory = MultiRef::new_with_history(10, 2);
     /// 
-    /// let a = unsafe {multiref.get_mut()};
-    /// let b = unsafe {multiref.get_mut()};
-    /// assert_eq!(*a, 10);
-    /// assert_eq!(*b, 10);
+    /// *unsafe {multihistory.get_mut()} += 1;
+    /// assert_eq!(multihistory.history(), vec![11]);
+    /// ```
     /// 
-    /// *a += 1;
-    /// assert_eq!(*a, 11);
-    /// assert_eq!(*b, 11);
+    #[cfg(feature = "history")]
+    pub fn new_with_history(object : T, capacity : usize) -> MultiHistory<T> where T : Clone {
+        return MultiHistory::new(object, capacity);
+    }
+
+    /// Create a `MultiBatched` that calls a callback with a clone of the value after every `batch` mutations made through its `get_mut()`.
+    /// The callback is registered with `MultiBatched::on_batch()`.
     /// 
-    /// *b += 2;
-    /// assert_eq!(*a, 13);
-    /// assert_eq!(*b, 13);
-    /// ```
-    ///
-    /// Multiple references.
-    /// ```
-    /// use pholib::MultiRef;
-    /// let multiref = MultiRef::new(10);
+    /// # Arguments
     /// 
-    /// let i = unsafe {multiref.get_ref()};
-    /// let a = unsafe {multiref.get_mut()};
-    /// let b = unsafe {multiref.get_mut()};
-    /// assert_eq!(*i, 10);
-    /// assert_eq!(*a, 10);
-    /// assert_eq!(*b, 10);
+    /// * `value` : The object to wrap.
+    /// * `batch` : The number of mutations between calls to the callback.
     /// 
-    /// *a += 1;
-    /// assert_eq!(*i, 11);
-    /// assert_eq!(*a, 11);
-    /// assert_eq!(*b, 11);
+    /// # Returns
+    /// 
+    /// The created `MultiBatched` instance.
+    /// 
+    /// # Panics
+    /// 
+    /// If `batch` is `0`.
+    /// 
+    /// # Examples
     /// 
-    /// *b += 2;
-    /// assert_eq!(*i, 13);
-    /// assert_eq!(*a, 13);
-    /// assert_eq!(*b, 13);
     /// ```
-    ///
-    pub unsafe fn get_mut(&self) -> &mut T {
-        return &mut *(&self.0).get();
+    /// use pholib::MultiRef;
+    /// let mut last = None;
+    /// let mut batched = MultiRef::new_batched(10, 2);
+    /// batched.on_batch(|value| last = Some(value));
+    /// 
+    /// *unsafe {batched.get_mut()} += 1;
+    /// *unsafe {batched.get_mut()} += 1;
+    /// drop(batched);
+    /// assert_eq!(last, Some(12));
+    /// ```
+    /// 
+    pub fn new_batched<'a>(value : T, batch : usize) -> MultiBatched<'a, T> where T : Clone {
+        return MultiBatched::new_batched(value, batch);
     }
 
     /// Return the wrapped value and drop the `MultiRef`.
@@ -194,7 +255,7 @@ impl<'l, T> MultiRef<T> {
     ///
     /// ```
     /// use pholib::MultiRef;
-    /// let multiref = unsafe {MultiRef::new(10)};
+    /// let multiref = MultiRef::new(10);
     /// 
     /// assert_eq!(multiref.unwrap(), 10);
     /// ```
@@ -216,118 +277,4854 @@ impl<'l, T> MultiRef<T> {
         return self.0.into_inner();
     }
 
-}
+    /// Get the address of the wrapped value.
+    /// 
+    /// # Returns
+    /// 
+    /// The address of the storage inside this `MultiRef`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(10);
+    /// 
+    /// assert_eq!(multiref.addr(), unsafe {multiref.get_ref()} as *const i32 as usize);
+    /// ```
+    /// 
+    pub fn addr(&self) -> usize {
+        return self.as_ptr() as usize;
+    }
 
+    /// Check whether two `MultiRef`s share the same storage, without comparing their values, like `Rc::ptr_eq`.
+    /// Useful for detecting accidental aliasing, e.g. before swapping two cells.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `a` : The first `MultiRef`.
+    /// * `b` : The second `MultiRef`.
+    /// 
+    /// # Returns
+    /// 
+    /// `true` if both refer to the same storage.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let a = MultiRef::new(10);
+    /// let b = MultiRef::new(10);
+    /// 
+    /// assert!(MultiRef::ptr_eq(&a, &a));
+    /// assert!(!MultiRef::ptr_eq(&a, &b));
+    /// ```
+    /// 
+    pub fn ptr_eq(a : &MultiRef<T>, b : &MultiRef<T>) -> bool {
+        return ptr::eq(a.0.get(), b.0.get());
+    }
 
+    /// Extend the wrapped collection through a shared reference.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `iter` : The items to add to the wrapped collection.
+    /// 
+    /// # Safety
+    /// 
+    /// The wrapped value is mutated through a shared reference, the same as with `get_mut()`.
+    /// Growing a collection can reallocate its storage, which leaves every outstanding reference
+    /// to its elements (e.g. `&get_ref()[0]`) dangling.
+    /// References to the collection itself stay valid.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(vec![1]);
+    /// 
+    /// let i = unsafe {multiref.get_ref()};
+    /// unsafe {multiref.extend_shared([2, 3])};
+    /// assert_eq!(*i, vec![1, 2, 3]);
+    /// ```
+    /// 
+    pub unsafe fn extend_shared<A>(&self, iter : impl IntoIterator<Item = A>) where T : Extend<A> {
+        self.get_mut().extend(iter);
+    }
 
+    /// Create a closure that adds its argument to the wrapped value each time it is called.
+    /// 
+    /// # Returns
+    /// 
+    /// The closure, which borrows this `MultiRef`.
+    /// 
+    /// # Safety
+    /// 
+    /// Every call of the closure mutates the wrapped value through a shared reference, the same as with `get_mut()`.
+    /// The closure can be stored and called long after this call, so the caller has to make sure
+    /// that no reference to the wrapped value is being used whenever it runs.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(0);
+    /// 
+    /// let mut on_score = unsafe {multiref.bind_add()};
+    /// on_score(10);
+    /// on_score(5);
+    /// drop(on_score);
+    /// assert_eq!(multiref.unwrap(), 15);
+    /// ```
+    /// 
+    pub unsafe fn bind_add(&self) -> impl FnMut(T) + '_ where T : AddAssign {
+        return move |rhs| *self.get_mut() += rhs;
+    }
 
+    /// Replace the wrapped value, returning the old one.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `value` : The new value to wrap.
+    /// 
+    /// # Returns
+    /// 
+    /// The value that was wrapped before the call.
+    /// 
+    /// # Safety
+    /// 
+    /// The wrapped value is overwritten through a shared reference.
+    /// References obtained from `get_ref()` or `get_mut()` observe the new value afterwards,
+    /// but references into the old value (e.g. into a `String`'s buffer) point into memory that
+    /// is now owned by the returned value, and dangle once it is dropped.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(10);
+    /// 
+    /// let i = unsafe {multiref.get_ref()};
+    /// assert_eq!(unsafe {multiref.replace(20)}, 10);
+    /// assert_eq!(*i, 20);
+    /// ```
+    /// 
+    pub unsafe fn replace(&self, value : T) -> T {
+        return std::mem::replace(self.get_mut(), value);
+    }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use std::thread;
+    /// Overwrite the wrapped value, dropping the old one.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `value` : The new value to wrap.
+    /// 
+    /// # Safety
+    /// 
+    /// See the warnings on `MultiRef`.
+    /// The old value is dropped while other references may still point into it,
+    /// so using a reference into the old value (e.g. into a `String`'s buffer) afterwards is undefined behaviour.
+    /// References to the wrapped value itself observe the new value.
+    /// 
+    /// The new value is in place before the old one is dropped, so the old value is dropped exactly once
+    /// and the `MultiRef` stays valid even if its destructor panics.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(10);
+    /// 
+    /// let i = unsafe {multiref.get_ref()};
+    /// unsafe {multiref.set(20)};
+    /// assert_eq!(*i, 20);
+    /// ```
+    /// 
+    pub unsafe fn set(&self, value : T) {
+        drop(self.replace(value));
+    }
 
-    #[test]
-    fn multiref() {unsafe {
-        let multiref = MultiRef::new(10);
+    /// Swap the wrapped values of two `MultiRef`s.
+    /// Swapping a `MultiRef` with itself does nothing.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `other` : The `MultiRef` to swap values with.
+    /// 
+    /// # Safety
+    /// 
+    /// Both wrapped values are mutated through shared references, the same as with `get_mut()`.
+    /// References obtained from either `MultiRef` observe the swapped values afterwards.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let a = MultiRef::new(1);
+    /// let b = MultiRef::new(2);
+    /// 
+    /// unsafe {a.swap(&b)};
+    /// assert_eq!(a.unwrap(), 2);
+    /// assert_eq!(b.unwrap(), 1);
+    /// ```
+    /// 
+    pub unsafe fn swap(&self, other : &MultiRef<T>) {
+        if MultiRef::ptr_eq(self, other) {
+            return;
+        }
+        ptr::swap(self.0.get(), other.0.get());
+    }
 
-        let a = multiref.get_mut();
-        let b = multiref.get_mut();
-        *a += 1;
-        *b += 2;
-        assert_eq!(multiref.unwrap(), 13);
-    }}
+    /// Transform the wrapped value into a new `MultiRef`, dropping this one.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `f` : Called with the wrapped value to produce the new one.
+    /// 
+    /// # Returns
+    /// 
+    /// A new `MultiRef` wrapping the transformed value.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(10);
+    /// 
+    /// let string = multiref.map(|n| n.to_string());
+    /// assert_eq!(string.unwrap(), "10");
+    /// ```
+    /// 
+    pub fn map<U, F : FnOnce(T) -> U>(self, f : F) -> MultiRef<U> {
+        return MultiRef::new(f(self.unwrap()));
+    }
 
-    #[test]
-    fn immut_and_mut() {unsafe {
-        let multiref = MultiRef::new(10);
+    /// Overwrite the wrapped value with a lazily constructed one, dropping the old one.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `f` : Called to construct the new value. If it panics, the old value is left in place.
+    /// 
+    /// # Safety
+    /// 
+    /// The same as `set()`. `f` runs before anything is overwritten, so it may still read the old value.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(String::from("a"));
+    /// 
+    /// unsafe {multiref.set_with(|| multiref.repeat(3))};
+    /// assert_eq!(multiref.unwrap(), "aaa");
+    /// ```
+    /// 
+    pub unsafe fn set_with(&self, f : impl FnOnce() -> T) {
+        self.set(f());
+    }
 
-        let i = multiref.get_ref();
+    /// Run a closure with an immutable reference to the wrapped value.
+    /// The reference can not escape the closure.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `f` : Called with a reference to the wrapped value.
+    /// 
+    /// # Returns
+    /// 
+    /// The value returned by `f`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(vec![1, 2, 3]);
+    /// 
+    /// let sum = multiref.with_ref(|v| v.iter().sum::<i32>());
+    /// unsafe {multiref.with_mut(|v| v.push(sum))};
+    /// assert_eq!(multiref.unwrap(), vec![1, 2, 3, 6]);
+    /// ```
+    /// 
+    pub fn with_ref<R>(&self, f : impl FnOnce(&T) -> R) -> R {
+        return f(unsafe {self.get_ref()});
+    }
+
+    /// Run a closure with a mutable reference to the wrapped value.
+    /// The reference can not escape the closure, which makes it easier to see where mutation happens.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `f` : Called with a mutable reference to the wrapped value.
+    /// 
+    /// # Returns
+    /// 
+    /// The value returned by `f`.
+    /// 
+    /// # Safety
+    /// 
+    /// The reference aliases every other reference obtained from this `MultiRef`, the same as with `get_mut()`.
+    /// 
+    /// If `f` panics, the wrapped value is left in whatever valid state `f` put it in, and the `MultiRef` can still be used.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(10);
+    /// 
+    /// let old = unsafe {multiref.with_mut(|n| {*n += 1; *n - 1})};
+    /// assert_eq!(old, 10);
+    /// assert_eq!(multiref.unwrap(), 11);
+    /// ```
+    /// 
+    pub unsafe fn with_mut<R>(&self, f : impl FnOnce(&mut T) -> R) -> R {
+        return f(self.get_mut());
+    }
+
+    /// Get several mutable references to the wrapped value at once.
+    /// 
+    /// # Returns
+    /// 
+    /// `N` mutable references, all to the same value.
+    /// 
+    /// # Safety
+    /// 
+    /// EVERY RETURNED REFERENCE POINTS AT THE SAME STORAGE.
+    /// They alias each other and every other reference obtained from this `MultiRef`.
+    /// The caller is responsible for preventing data races and any other undefined behaviour this can cause.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(0);
+    /// 
+    /// let [a, b, c] = unsafe {multiref.get_many_mut()};
+    /// *a += 1;
+    /// *b += 2;
+    /// *c += 3;
+    /// assert_eq!(multiref.unwrap(), 6);
+    /// ```
+    /// 
+    pub unsafe fn get_many_mut<const N : usize>(&self) -> [&mut T; N] {
+        return std::array::from_fn(|_| &mut *self.0.get());
+    }
+
+    /// Return the wrapped value and drop the `MultiRef`, mirroring `Rc::try_unwrap`.
+    /// A `MultiRef` has a single owner, so this never fails.
+    /// It exists so that code written against it keeps working if the cell is replaced by a shared-ownership variant.
+    /// 
+    /// # Returns
+    /// 
+    /// Always `Ok` with the wrapped value.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(10);
+    /// 
+    /// assert_eq!(multiref.try_unwrap().ok(), Some(10));
+    /// ```
+    /// 
+    pub fn try_unwrap(self) -> Result<T, MultiRef<T>> {
+        return Ok(self.unwrap());
+    }
+
+    /// Move the wrapped value into a new heap allocation and leak it as a raw pointer, like `Box::into_raw`.
+    /// The `MultiRef` is consumed.
+    /// 
+    /// After this call the caller owns the allocation.
+    /// It is only freed (and the value only dropped) by passing the pointer to `MultiRef::from_raw` exactly once.
+    /// Until then, the pointer can be cast to a `usize` or `*mut c_void`, copied and written through freely.
+    /// 
+    /// # Returns
+    /// 
+    /// A pointer to the heap allocation holding the value.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(10);
+    /// 
+    /// let ptr = multiref.into_raw();
+    /// unsafe {*ptr += 1};
+    /// let multiref = unsafe {MultiRef::from_raw(ptr)};
+    /// assert_eq!(multiref.unwrap(), 11);
+    /// ```
+    /// 
+    pub fn into_raw(self) -> *mut T {
+        return Box::into_raw(Box::new(self.unwrap()));
+    }
+
+    /// Take back ownership of a value leaked by `MultiRef::into_raw`, like `Box::from_raw`.
+    /// The value is moved back into a new `MultiRef` and the heap allocation is freed.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `ptr` : A pointer returned by `MultiRef::into_raw`.
+    /// 
+    /// # Returns
+    /// 
+    /// A new `MultiRef` wrapping the value.
+    /// 
+    /// # Safety
+    /// 
+    /// * `ptr` must come from `MultiRef::into_raw` with the same `T`.
+    /// * It must not have been passed to `from_raw` before. Doing so twice frees the allocation twice and drops the value twice.
+    /// * Every copy of `ptr` dangles after this call.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let address = MultiRef::new(String::from("state")).into_raw() as usize;
+    /// 
+    /// let multiref = unsafe {MultiRef::from_raw(address as *mut String)};
+    /// assert_eq!(multiref.unwrap(), "state");
+    /// ```
+    /// 
+    pub unsafe fn from_raw(ptr : *mut T) -> MultiRef<T> {
+        return MultiRef::new(*Box::from_raw(ptr));
+    }
+
+    /// Move the wrapped value onto the heap and leak it, like `Box::leak`.
+    /// The `MultiRef` is consumed.
+    /// 
+    /// The memory is intentionally never reclaimed and the value is never dropped.
+    /// This is meant for long-lived global state created during program setup.
+    /// The value moves to a new address, so raw pointers taken from the `MultiRef` beforehand (e.g. with `as_mut_ptr()`)
+    /// do not point to the leaked value.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable reference to the leaked value, valid for any lifetime `T` outlives (including `'static`).
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let counter : &'static mut u32 = MultiRef::new(0).leak();
+    /// 
+    /// *counter += 1;
+    /// assert_eq!(*counter, 1);
+    /// ```
+    /// 
+    pub fn leak<'l>(self) -> &'l mut T where T : 'l {
+        return Box::leak(Box::new(self.unwrap()));
+    }
+
+    /// Transform the wrapped value into a new `MultiRef` with a fallible function, dropping this one.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `f` : Called with the wrapped value to produce the new one.
+    /// 
+    /// # Returns
+    /// 
+    /// A new `MultiRef` wrapping the transformed value, or the error returned by `f`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new("10");
+    /// 
+    /// let number = multiref.try_map(|s| s.parse::<i32>()).unwrap();
+    /// assert_eq!(number.unwrap(), 10);
+    /// assert!(MultiRef::new("x").try_map(|s| s.parse::<i32>()).is_err());
+    /// ```
+    /// 
+    pub fn try_map<U, E, F : FnOnce(T) -> Result<U, E>>(self, f : F) -> Result<MultiRef<U>, E> {
+        return f(self.unwrap()).map(MultiRef::new);
+    }
+
+    /// Reinterpret the bits of the wrapped value as another type, consuming the `MultiRef`.
+    /// 
+    /// THIS IS WILDLY UNSAFE. It is a `transmute` between arbitrary types of the same size.
+    /// The value is copied out of the cell, so the alignments of `T` and `U` do not have to match.
+    /// 
+    /// # Returns
+    /// 
+    /// A new `MultiRef` wrapping the reinterpreted value. The destructor of `T` is not run.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `T` and `U` have different sizes.
+    /// 
+    /// # Safety
+    /// 
+    /// The bits of the wrapped value must be a valid `U`, with the same requirements as `std::mem::transmute`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(1.0f32);
+    /// 
+    /// let bits = unsafe {multiref.cast::<u32>()};
+    /// assert_eq!(bits.unwrap(), 1.0f32.to_bits());
+    /// ```
+    /// 
+    pub unsafe fn cast<U>(self) -> MultiRef<U> {
+        assert_eq!(mem::size_of::<T>(), mem::size_of::<U>(), "can not cast between types of different sizes");
+        return MultiRef::new(mem::transmute_copy(&ManuallyDrop::new(self.unwrap())));
+    }
+
+    /// Apply a closure to the wrapped value in place.
+    /// The reference can not escape the closure. To return a value out of it, use `with_mut()`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `f` : Called with a mutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The same as `with_mut()`.
+    /// The closure is not guarded against re-entrancy: if it captures the `MultiRef`, it can take more aliasing
+    /// references to the same value (or call `update()` again) while its own reference is live.
+    /// 
+    /// # Examples
+    /// 
+    /// Counter
+    /// ```
+    /// use pholib::MultiRef;
+    /// let counter = MultiRef::new(0);
+    /// 
+    /// for _ in 0..3 {
+    ///     unsafe {counter.update(|count| *count += 1)};
+    /// }
+    /// assert_eq!(counter.get(), 3);
+    /// ```
+    /// 
+    /// Map
+    /// ```
+    /// use pholib::MultiRef;
+    /// use std::collections::HashMap;
+    /// let multiref = MultiRef::new(HashMap::new());
+    /// 
+    /// unsafe {multiref.update(|map| *map.entry("hits").or_insert(0) += 1)};
+    /// assert_eq!(multiref["hits"], 1);
+    /// ```
+    /// 
+    pub unsafe fn update(&self, f : impl FnOnce(&mut T)) {
+        f(self.get_mut());
+    }
+
+
+    /// Project into a part of the wrapped value, returning a `MultiRef` view backed by the same storage.
+    /// The view can be handed to code that only needs that part and used with `get_ref()`/`get_mut()` as usual.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `f` : Called with a mutable reference to the wrapped value, returns a mutable reference to the part to project into.
+    /// 
+    /// # Returns
+    /// 
+    /// A `MultiRef` view of the part, borrowed from this `MultiRef`.
+    /// 
+    /// # Safety
+    /// 
+    /// `f` receives a mutable reference obtained the same way as with `get_mut()`.
+    /// The view aliases the wrapped value, so replacing the whole value (or the part's owner, e.g. a `Vec`'s buffer)
+    /// while the view is in use leaves it dangling.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// struct Player {
+    ///     pub name   : String,
+    ///     pub health : u32
+    /// }
+    /// let player = MultiRef::new(Player {name : String::from("a"), health : 100});
+    /// 
+    /// let health = unsafe {player.map_ref(|player| &mut player.health)};
+    /// unsafe {*health.get_mut() -= 30};
+    /// assert_eq!(player.health, 70);
+    /// ```
+    /// 
+    pub unsafe fn map_ref<U>(&self, f : impl FnOnce(&mut T) -> &mut U) -> &MultiRef<U> {
+        return MultiRef::from_mut(f(self.get_mut()));
+    }
+
+
+    /// Create a `MultiRef` whose value is not initialised yet, to be filled in later with `write()`.
+    /// Useful when the cell has to exist (e.g. so its address can be taken) before the value can be built.
+    /// 
+    /// # Returns
+    /// 
+    /// A `MultiRef` wrapping an uninitialised `T`. Dropping it never drops a `T`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::<i32>::new_uninit();
+    /// 
+    /// unsafe {multiref.write(10)};
+    /// assert_eq!(unsafe {multiref.assume_init()}.unwrap(), 10);
+    /// ```
+    /// 
+    pub const fn new_uninit() -> MultiRef<MaybeUninit<T>> {
+        return MultiRef::new(MaybeUninit::uninit());
+    }
+
+
+    /// Call a closure with the wrapped value repeatedly until it returns `Some`, for optimistic retry loops.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `f` : Called with a fresh mutable reference to the wrapped value on every attempt. Returns `None` to try again.
+    /// 
+    /// # Returns
+    /// 
+    /// The value from the first attempt that returned `Some`.
+    /// 
+    /// # Safety
+    /// 
+    /// The same as `with_mut()`. Changes made by failed attempts are not rolled back.
+    /// If `f` never returns `Some`, this never returns.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(vec![1, 2, 3]);
+    /// 
+    /// let last_even = unsafe {multiref.with_mut_retry(|v| v.pop().filter(|x| x % 2 == 0))};
+    /// assert_eq!(last_even, 2);
+    /// assert_eq!(multiref.unwrap(), vec![1]);
+    /// ```
+    /// 
+    pub unsafe fn with_mut_retry<R>(&self, mut f : impl FnMut(&mut T) -> Option<R>) -> R {
+        loop {
+            if let Some(result) = f(self.get_mut()) {
+                return result;
+            }
+        }
+    }
+
+
+    /// Create a `MultiRef` whose value has every byte set to zero.
+    /// Useful for large plain-data buffers, e.g. `[u8; 65536]`, that would otherwise be written out element by element.
+    /// The `MultiRef` itself is still returned by value. Use `MultiBox::new_zeroed()` for buffers too large for the stack.
+    /// 
+    /// # Returns
+    /// 
+    /// A `MultiRef` wrapping an all-zero `T`.
+    /// 
+    /// # Safety
+    /// 
+    /// All-zero bytes must be a valid `T`, with the same requirements as `std::mem::zeroed`.
+    /// This holds for integers, floats, raw pointers and arrays or structs made only of them.
+    /// It does not hold for references, `Box`, `Vec`, `String`, function pointers or `NonZero*` types,
+    /// and creating one of those zeroed is immediate undefined behaviour.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let buffer = unsafe {MultiRef::<[u8; 65536]>::new_zeroed()};
+    /// 
+    /// assert!(buffer.iter().all(|byte| *byte == 0));
+    /// ```
+    /// 
+    pub unsafe fn new_zeroed() -> MultiRef<T> {
+        // Zero the `MultiRef` in place, instead of creating a zeroed `T` and moving it in.
+        return MaybeUninit::<MultiRef<T>>::zeroed().assume_init();
+    }
+
+    /// Create a `MultiRef` whose value has every byte set to zero, for types that are marked as `Zeroable`.
+    /// This is the safe version of `new_zeroed()`.
+    /// 
+    /// # Returns
+    /// 
+    /// A `MultiRef` wrapping an all-zero `T`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let counters = MultiRef::<[u32; 16]>::zeroed();
+    /// 
+    /// unsafe {counters.get_mut()[3] += 1};
+    /// assert_eq!(counters.unwrap()[..4], [0, 0, 0, 1]);
+    /// ```
+    /// 
+    pub fn zeroed() -> MultiRef<T> where T : Zeroable {
+        return unsafe {MultiRef::new_zeroed()};
+    }
+
+
+    /// Move the wrapped value onto the heap and leak it, like `leak()`, but hand out a shared reference.
+    /// The `MultiRef` is consumed. The memory is never reclaimed and the value is never dropped.
+    /// 
+    /// # Returns
+    /// 
+    /// An immutable reference to the leaked value, valid for any lifetime `T` outlives (including `'static`).
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let name : &'static String = MultiRef::new(String::from("app")).leak_ref();
+    /// 
+    /// assert_eq!(name, "app");
+    /// ```
+    /// 
+    pub fn leak_ref<'l>(self) -> &'l T where T : 'l {
+        return self.leak();
+    }
+
+
+    /// Move the `MultiRef` into a pinned heap allocation, so `get_pin_mut()` can be used on it.
+    /// 
+    /// # Returns
+    /// 
+    /// The pinned `MultiRef`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// use std::future::Future;
+    /// use std::task::{Context, Poll, Waker};
+    /// let pinned = MultiRef::new(async {10}).pinned();
+    /// 
+    /// let mut context = Context::from_waker(Waker::noop());
+    /// let poll = unsafe {pinned.get_pin_mut()}.poll(&mut context);
+    /// assert_eq!(poll, Poll::Ready(10));
+    /// ```
+    /// 
+    pub fn pinned(self) -> Pin<Box<MultiRef<T>>> {
+        return Box::pin(self);
+    }
+
+    /// Get a pinned mutable reference to the wrapped value, for APIs that take `Pin<&mut T>` (e.g. `Future::poll`).
+    /// See the type-level docs on pinning.
+    /// 
+    /// # Returns
+    /// 
+    /// A pinned mutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The same as `get_mut()`. In addition, unless `T` is `Unpin`, the wrapped value must not move again
+    /// until it is dropped: the `MultiRef` must not be moved, unwrapped or have its value replaced or swapped out.
+    /// This is guaranteed when the `MultiRef` was pinned with `pinned()`, or when using `MultiPin` or `MultiBox` instead.
+    /// 
+    pub unsafe fn get_pin_mut(&self) -> Pin<&mut T> {
+        return Pin::new_unchecked(self.get_mut());
+    }
+
+
+    /// Overwrite the wrapped value, but only if it is equal to `expected`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `expected` : The value the wrapped value must have for it to be overwritten.
+    /// * `new`      : The value to write.
+    /// 
+    /// # Returns
+    /// 
+    /// `Ok` with the previous value if it was overwritten, or `Err` giving back `new` if it was not.
+    /// 
+    /// # Safety
+    /// 
+    /// The value is overwritten through a shared reference, the same as with `replace()`.
+    /// This only looks atomic: the comparison and the write are separate steps, and there are no guarantees across threads.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let state = MultiRef::new("idle");
+    /// 
+    /// assert_eq!(unsafe {state.compare_and_set(&"idle", "running")}, Ok("idle"));
+    /// assert_eq!(unsafe {state.compare_and_set(&"idle", "stopped")}, Err("stopped"));
+    /// assert_eq!(state.unwrap(), "running");
+    /// ```
+    /// 
+    pub unsafe fn compare_and_set(&self, expected : &T, new : T) -> Result<T, T> where T : PartialEq {
+        if self.get_ref() != expected {
+            return Err(new);
+        }
+        return Ok(self.replace(new));
+    }
+
+    /// Update the wrapped value in place, but only if it is equal to `expected`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `expected` : The value the wrapped value must have for it to be updated.
+    /// * `f`        : Called with a mutable reference to the wrapped value if it matched.
+    /// 
+    /// # Returns
+    /// 
+    /// `true` if `f` was called.
+    /// 
+    /// # Safety
+    /// 
+    /// The same as `compare_and_set()` and `update()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let queue = MultiRef::new(vec![1]);
+    /// 
+    /// assert!(unsafe {queue.compare_and_update(&vec![1], |queue| queue.push(2))});
+    /// assert!(!unsafe {queue.compare_and_update(&vec![1], |queue| queue.clear())});
+    /// assert_eq!(queue.unwrap(), vec![1, 2]);
+    /// ```
+    /// 
+    pub unsafe fn compare_and_update(&self, expected : &T, f : impl FnOnce(&mut T)) -> bool where T : PartialEq {
+        if self.get_ref() != expected {
+            return false;
+        }
+        f(self.get_mut());
+        return true;
+    }
+
+
+    /// Check that this `MultiRef` does not share storage with any of the given pointers, for testing non-aliasing invariants.
+    /// Only checked in debug builds. In release builds this does nothing.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `others` : Pointers to values that must not be this `MultiRef`'s value, e.g. from other `MultiRef`s' `as_ptr()`.
+    /// 
+    /// # Panics
+    /// 
+    /// In debug builds, if any pointer in `others` points to the wrapped value.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let a = MultiRef::new(1);
+    /// let b = MultiRef::new(1);
+    /// 
+    /// a.assert_distinct_from(&[b.as_ptr()]);
+    /// ```
+    /// 
+    pub fn assert_distinct_from(&self, others : &[*const T]) {
+        if cfg!(debug_assertions) {
+            if let Some(index) = others.iter().position(|other| ptr::eq(*other, self.as_ptr())) {
+                panic!("MultiRef at {:p} is aliased by pointer {} of {}", self.as_ptr(), index, others.len());
+            }
+        }
+    }
+
+
+    /// Swap the wrapped value with the value behind a raw pointer, e.g. a buffer owned by foreign code.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `ptr` : Points to the value to swap with.
+    /// 
+    /// # Safety
+    /// 
+    /// `ptr` must be non-null, properly aligned for `T`, and valid for reads and writes of an initialised `T`,
+    /// with the same requirements as `std::ptr::swap`. It may point to the wrapped value itself, in which case nothing changes.
+    /// The wrapped value is mutated through a shared reference, the same as with `get_mut()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new([1u8; 4]);
+    /// let mut buffer = [2u8; 4];
+    /// 
+    /// unsafe {multiref.swap_with_ptr(&mut buffer)};
+    /// assert_eq!(buffer, [1; 4]);
+    /// assert_eq!(multiref.unwrap(), [2; 4]);
+    /// ```
+    /// 
+    pub unsafe fn swap_with_ptr(&self, ptr : *mut T) {
+        ptr::swap(self.as_mut_ptr(), ptr);
+    }
+
+}
+
+
+impl<T : ?Sized> MultiRef<T> {
+
+    /// Get an immutable reference to the wrapped value.
+    /// Can be used simultaneously with `get_mut()`s or other `get_ref()`s.
+    ///
+    /// # Returns
+    /// 
+    /// An immutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The caller must make sure that the wrapped value is not being written to through another reference
+    /// while the returned reference is in use.
+    /// 
+    /// # Examples
+    ///
+    /// Basic
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(10);
+    /// 
+    /// let i = unsafe {multiref.get_ref()};
+    /// assert_eq!(*i, 10);
+    /// ```
+    ///
+    /// Multiple immutable references.
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(10);
+    /// 
+    /// let a = unsafe {multiref.get_ref()};
+    /// let b = unsafe {multiref.get_ref()};
+    /// assert_eq!(*a, 10);
+    /// assert_eq!(*b, 10);
+    /// ```
+    /// 
+    pub unsafe fn get_ref(&self) -> &T {
+        return & *self.0.get();
+    }
+
+
+    /// Get a mutable reference to the wrapped value.
+    /// Can be used simultaneously with `get_ref()`s or other `get_mut()`s.
+    ///
+    /// # Returns
+    /// 
+    /// A mutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The returned reference aliases every other reference obtained from this `MultiRef`.
+    /// The caller is responsible for preventing data races and any other undefined behaviour this can cause.
+    /// 
+    /// # Examples
+    ///
+    /// Basic
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(10);
+    /// 
+    /// let a = unsafe {multiref.get_mut()};
+    /// let b = unsafe {multiref.get_mut()};
+    /// assert_eq!(*a, 10);
+    /// assert_eq!(*b, 10);
+    /// 
+    /// *a += 1;
+    /// assert_eq!(*a, 11);
+    /// assert_eq!(*b, 11);
+    /// 
+    /// *b += 2;
+    /// assert_eq!(*a, 13);
+    /// assert_eq!(*b, 13);
+    /// ```
+    ///
+    /// Multiple references.
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(10);
+    /// 
+    /// let i = unsafe {multiref.get_ref()};
+    /// let a = unsafe {multiref.get_mut()};
+    /// let b = unsafe {multiref.get_mut()};
+    /// assert_eq!(*i, 10);
+    /// assert_eq!(*a, 10);
+    /// assert_eq!(*b, 10);
+    /// 
+    /// *a += 1;
+    /// assert_eq!(*i, 11);
+    /// assert_eq!(*a, 11);
+    /// assert_eq!(*b, 11);
+    /// 
+    /// *b += 2;
+    /// assert_eq!(*i, 13);
+    /// assert_eq!(*a, 13);
+    /// assert_eq!(*b, 13);
+    /// ```
+    ///
+    pub unsafe fn get_mut(&self) -> &mut T {
+        return &mut *self.0.get();
+    }
+
+
+    /// Get a raw pointer to the wrapped value, without creating a reference.
+    /// This is the preferred way to do pointer arithmetic or volatile access on the wrapped value.
+    /// 
+    /// # Returns
+    /// 
+    /// A raw pointer to the wrapped value. Dereferencing it is up to the caller.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(10);
+    /// 
+    /// assert_eq!(unsafe {*multiref.as_ptr()}, 10);
+    /// ```
+    /// 
+    pub const fn as_ptr(&self) -> *const T {
+        return self.0.get();
+    }
+
+
+    /// Get a mutable raw pointer to the wrapped value, without creating a reference.
+    /// This is the preferred way to do pointer arithmetic or volatile access on the wrapped value.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable raw pointer to the wrapped value. Dereferencing it is up to the caller.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(10);
+    /// 
+    /// unsafe {multiref.as_mut_ptr().write_volatile(11)};
+    /// assert_eq!(multiref.unwrap(), 11);
+    /// ```
+    /// 
+    pub const fn as_mut_ptr(&self) -> *mut T {
+        return self.0.get();
+    }
+
+
+    /// View exclusively borrowed data as a `MultiRef`, without moving it, like `Cell::from_mut`.
+    /// While the returned reference is alive, the data can be aliased through it. Afterwards the original binding is usable again.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `value` : The data to view.
+    /// 
+    /// # Returns
+    /// 
+    /// A `MultiRef` view of the data, borrowed for as long as `value` was.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let mut count = 0;
+    /// 
+    /// let multiref = MultiRef::from_mut(&mut count);
+    /// let a = unsafe {multiref.get_mut()};
+    /// let b = unsafe {multiref.get_mut()};
+    /// *a += 1;
+    /// *b += 2;
+    /// assert_eq!(count, 3);
+    /// ```
+    /// 
+    pub fn from_mut(value : &mut T) -> &MultiRef<T> {
+        // `MultiRef<T>` is `repr(transparent)` over `UnsafeCell<T>`, which has the same layout as `T`.
+        return unsafe {&*(value as *mut T as *const MultiRef<T>)};
+    }
+
+}
+
+
+impl<T : Default> MultiRef<T> {
+
+    /// Return the wrapped value and replace it with `T::default()`.
+    /// 
+    /// # Returns
+    /// 
+    /// The value that was wrapped before the call.
+    /// 
+    /// # Safety
+    /// 
+    /// The same as `replace()`.
+    /// Any reference obtained from `get_ref()` or `get_mut()` will observe the default value afterwards,
+    /// and references into the old value (e.g. into a `Vec`'s buffer) are left dangling once it is dropped.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(10);
+    /// 
+    /// let i = unsafe {multiref.get_ref()};
+    /// assert_eq!(unsafe {multiref.take()}, 10);
+    /// assert_eq!(*i, 0);
+    /// ```
+    /// 
+    pub unsafe fn take(&self) -> T {
+        return self.replace(T::default());
+    }
+
+    /// Replace the wrapped value with a function of the old value.
+    /// 
+    /// The old value is taken out with `take()` before `f` runs, so the `MultiRef` holds `T::default()` while `f` runs.
+    /// If `f` panics, the old value is dropped and the `MultiRef` is left holding the default value,
+    /// never a duplicated or uninitialised one.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `f` : Called with the old value to produce the new one.
+    /// 
+    /// # Safety
+    /// 
+    /// The same as `replace()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(vec![1, 2]);
+    /// 
+    /// unsafe {multiref.replace_with(|mut v| {v.reverse(); v})};
+    /// assert_eq!(multiref.unwrap(), vec![2, 1]);
+    /// ```
+    /// 
+    pub unsafe fn replace_with<F : FnOnce(T) -> T>(&self, f : F) {
+        let value = f(self.take());
+        self.set(value);
+    }
+
+
+    /// Wrap `T::default()`.
+    /// Safe for the same reason as `new()`: constructing a `MultiRef` can not cause undefined behaviour by itself.
+    /// Useful with a turbofish where `MultiRef::default()` would need a type annotation.
+    /// 
+    /// # Returns
+    /// 
+    /// A new `MultiRef` wrapping the default value of `T`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::<Vec<i32>>::new_default();
+    /// 
+    /// assert!(multiref.is_empty());
+    /// ```
+    /// 
+    pub fn new_default() -> MultiRef<T> {
+        return MultiRef::new(T::default());
+    }
+
+}
+
+
+impl<A, B, C> MultiRef<(A, B, C)> {
+
+    /// Get mutable references to each of the three fields of the wrapped tuple.
+    /// 
+    /// # Returns
+    /// 
+    /// Mutable references to the first, second and third field.
+    /// 
+    /// # Safety
+    /// 
+    /// The returned references do not overlap each other, but they alias every other reference obtained from this `MultiRef`.
+    /// The caller is responsible for preventing data races and any other undefined behaviour this can cause.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new((1, String::from("a"), false));
+    /// 
+    /// let (a, b, c) = unsafe {multiref.three_mut()};
+    /// *a += 1;
+    /// b.push('b');
+    /// *c = true;
+    /// assert_eq!(multiref.unwrap(), (2, String::from("ab"), true));
+    /// ```
+    /// 
+    pub unsafe fn three_mut(&self) -> (&mut A, &mut B, &mut C) {
+        let tuple = self.0.get();
+        return (
+            &mut *ptr::addr_of_mut!((*tuple).0),
+            &mut *ptr::addr_of_mut!((*tuple).1),
+            &mut *ptr::addr_of_mut!((*tuple).2)
+        );
+    }
+
+}
+
+
+impl<T> MultiRef<Vec<T>> {
+
+    /// Move an element of the wrapped vector to the back, keeping the order of the other elements.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `index` : The index of the element to move.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `index` is out of bounds.
+    /// 
+    /// # Safety
+    /// 
+    /// The wrapped vector is modified through a shared reference.
+    /// References to elements obtained earlier will point at different elements afterwards.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(vec![1, 2, 3, 4]);
+    /// 
+    /// unsafe {multiref.move_to_back(1)};
+    /// assert_eq!(multiref.unwrap(), vec![1, 3, 4, 2]);
+    /// ```
+    /// 
+    pub unsafe fn move_to_back(&self, index : usize) {
+        let vec = self.get_mut();
+        let element = vec.remove(index);
+        vec.push(element);
+    }
+
+    /// Compare the wrapped vector against an earlier snapshot of it.
+    /// 
+    /// Only positions that exist in both are compared, so added or removed elements are not reported.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `old` : The snapshot to compare against.
+    /// 
+    /// # Returns
+    /// 
+    /// An `(index, old_value, new_value)` entry for each position where the values differ.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(vec![1, 2, 3]);
+    /// 
+    /// let snapshot = unsafe {multiref.get_ref()}.clone();
+    /// let a = unsafe {multiref.get_mut()};
+    /// a[1] = 5;
+    /// assert_eq!(multiref.diff_since(&snapshot), vec![(1, 2, 5)]);
+    /// ```
+    /// 
+    pub fn diff_since(&self, old : &[T]) -> Vec<(usize, T, T)> where T : PartialEq + Clone {
+        return unsafe {self.get_ref()}.iter().zip(old).enumerate()
+            .filter(|(_, (new, old))| new != old)
+            .map(|(index, (new, old))| (index, old.clone(), new.clone()))
+            .collect();
+    }
+
+    /// Iterate over mutable references to the elements of the wrapped vector, through a shared reference.
+    /// 
+    /// # Returns
+    /// 
+    /// An iterator over the elements.
+    /// 
+    /// # Safety
+    /// 
+    /// The yielded references alias every other reference obtained from this `MultiRef`, including ones
+    /// yielded by other iterators, so the same collection can be iterated in nested loops.
+    /// The iterator is invalidated if the vector is resized or reallocated while it is alive
+    /// (e.g. by `push` through `get_mut()`), after which using it is undefined behaviour.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(vec![1, 2, 3]);
+    /// 
+    /// for x in unsafe {multiref.iter_mut_aliased()} {
+    ///     *x *= 10;
+    /// }
+    /// assert_eq!(multiref.unwrap(), vec![10, 20, 30]);
+    /// ```
+    /// 
+    pub unsafe fn iter_mut_aliased(&self) -> slice::IterMut<'_, T> {
+        return self.get_mut().iter_mut();
+    }
+
+    /// Replace an element of the wrapped vector, returning the old element.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `index` : The index of the element.
+    /// * `value` : The new element.
+    /// 
+    /// # Returns
+    /// 
+    /// The element that was at `index` before the call.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `index` is out of bounds.
+    /// 
+    /// # Safety
+    /// 
+    /// The element is overwritten through a shared reference, the same as with `replace()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(vec![1, 2, 3]);
+    /// 
+    /// assert_eq!(unsafe {multiref.replace_at(1, 5)}, 2);
+    /// assert_eq!(multiref.unwrap(), vec![1, 5, 3]);
+    /// ```
+    /// 
+    pub unsafe fn replace_at(&self, index : usize, value : T) -> T {
+        return std::mem::replace(&mut self.get_mut()[index], value);
+    }
+
+    /// Split the wrapped vector into three non-overlapping mutable slices, `[.. i]`, `[i .. j]` and `[j ..]`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `i` : The start of the second slice.
+    /// * `j` : The start of the third slice.
+    /// 
+    /// # Returns
+    /// 
+    /// The three slices.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics unless `i <= j <= len`.
+    /// 
+    /// # Safety
+    /// 
+    /// The slices do not overlap each other, but they alias every other reference obtained from this `MultiRef`.
+    /// They dangle if the vector is reallocated while they are in use.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(vec![3, 1, 2, 5, 4]);
+    /// 
+    /// let (low, pivot, high) = unsafe {multiref.split_three_mut(2, 3)};
+    /// low.sort();
+    /// high.sort();
+    /// assert_eq!(pivot, &[2]);
+    /// assert_eq!(multiref.unwrap(), vec![1, 3, 2, 4, 5]);
+    /// ```
+    /// 
+    pub unsafe fn split_three_mut(&self, i : usize, j : usize) -> (&mut [T], &mut [T], &mut [T]) {
+        assert!(i <= j, "split indices out of order: {} > {}", i, j);
+        let (first, rest) = self.get_mut().split_at_mut(i);
+        let (second, third) = rest.split_at_mut(j - i);
+        return (first, second, third);
+    }
+
+
+    /// Reduce every overlapping window of the wrapped vector to a single value, e.g. for smoothing filters.
+    /// The wrapped vector is not modified.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `size` : The number of elements in each window.
+    /// * `f`    : Called with each window in order, returns its reduced value.
+    /// 
+    /// # Returns
+    /// 
+    /// The reduced values, one per window. Empty if the vector is shorter than `size`.
+    /// 
+    /// # Panics
+    /// 
+    /// If `size` is 0.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(vec![1, 2, 3, 4]);
+    /// 
+    /// assert_eq!(multiref.apply_windows(2, |w| w[0] + w[1]), vec![3, 5, 7]);
+    /// ```
+    /// 
+    pub fn apply_windows<U>(&self, size : usize, f : impl FnMut(&[T]) -> U) -> Vec<U> {
+        return unsafe {self.get_ref()}.windows(size).map(f).collect();
+    }
+
+    /// View the elements of the wrapped `Vec` as a slice of `MultiRef`s, one per element.
+    /// See `MultiRef::<[T]>::as_slice_of_refs()`.
+    /// 
+    /// # Returns
+    /// 
+    /// A slice of element cells, borrowed for as long as this `MultiRef`.
+    /// 
+    /// # Safety
+    /// 
+    /// The element cells point into the current buffer of the `Vec`. It must not be reallocated, shrunk or dropped
+    /// through another reference (e.g. with `get_mut().push()`) while the returned slice is in use.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(vec![1, 2, 3]);
+    /// 
+    /// let cells = unsafe {multiref.as_slice_of_refs()};
+    /// unsafe {cells[1].set(20)};
+    /// assert_eq!(multiref.unwrap(), vec![1, 20, 3]);
+    /// ```
+    /// 
+    pub unsafe fn as_slice_of_refs(&self) -> &[MultiRef<T>] {
+        let elements = (*self.as_mut_ptr()).as_mut_slice();
+        return MultiRef::from_mut(elements).as_slice_of_refs();
+    }
+
+    /// Get mutable references to several distinct elements of the wrapped `Vec`, like `slice::get_disjoint_mut`.
+    /// Unlike indexing into `get_mut()` several times, only the elements are borrowed mutably, not the `Vec` itself.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `indices` : The indices of the elements.
+    /// 
+    /// # Returns
+    /// 
+    /// Mutable references to the elements, in the order of `indices`,
+    /// or `None` if an index is out of range or appears more than once.
+    /// 
+    /// # Safety
+    /// 
+    /// The returned references do not overlap each other, but they alias every other reference obtained from this `MultiRef`.
+    /// The `Vec` must not be reallocated, shrunk or dropped through another reference while they are in use.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(vec![1, 2, 3]);
+    /// 
+    /// let [a, b, c] = unsafe {multiref.get_disjoint_mut([2, 1, 0])}.unwrap();
+    /// std::mem::swap(a, c);
+    /// *b *= 10;
+    /// assert_eq!(multiref.unwrap(), vec![3, 20, 1]);
+    /// ```
+    /// 
+    pub unsafe fn get_disjoint_mut<const K : usize>(&self, indices : [usize; K]) -> Option<[&mut T; K]> {
+        return MultiRef::from_mut((*self.as_mut_ptr()).as_mut_slice()).get_disjoint_mut(indices);
+    }
+
+}
+
+
+/// Fold the value of one `MultiRef` into the value of another.
+/// 
+/// # Arguments
+/// 
+/// * `dst` : The `MultiRef` whose value is updated.
+/// * `src` : The `MultiRef` whose value is read.
+/// * `f`   : The reducer, called with the value of `dst` and the value of `src`.
+/// 
+/// # Safety
+/// 
+/// The value of `dst` is mutated through a shared reference, the same as with `get_mut()`.
+/// If `dst` and `src` are the same cell, `f` receives a mutable and an immutable reference to the same value.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::{MultiRef, reduce_into};
+/// let total = MultiRef::new(10);
+/// let other = MultiRef::new(5);
+/// 
+/// unsafe {reduce_into(&total, &other, |a, b| *a += *b)};
+/// assert_eq!(total.unwrap(), 15);
+/// ```
+/// 
+pub unsafe fn reduce_into<T>(dst : &MultiRef<T>, src : &MultiRef<T>, f : impl FnOnce(&mut T, &T)) {
+    f(dst.get_mut(), src.get_ref());
+}
+
+
+impl<T, const N : usize> MultiRef<[T; N]> {
+
+    /// Get mutable references to several distinct elements of the wrapped array.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `indices` : The indices of the elements.
+    /// 
+    /// # Returns
+    /// 
+    /// Mutable references to the elements, in the order of `indices`.
+    /// 
+    /// # Errors
+    /// 
+    /// * `IndexError::OutOfRange` if an index is not less than `N`.
+    /// * `IndexError::Duplicate` if an index appears more than once.
+    /// 
+    /// # Safety
+    /// 
+    /// The returned references do not overlap each other, but they alias every other reference obtained from this `MultiRef`.
+    /// The caller is responsible for preventing data races and any other undefined behaviour this can cause.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::{MultiRef, IndexError};
+    /// let multiref = MultiRef::new([1, 2, 3, 4]);
+    /// 
+    /// let [a, b] = unsafe {multiref.try_get_many_mut([3, 0])}.unwrap();
+    /// std::mem::swap(a, b);
+    /// assert_eq!(unsafe {multiref.try_get_many_mut([1, 1])}.err(), Some(IndexError::Duplicate(1)));
+    /// assert_eq!(multiref.unwrap(), [4, 2, 3, 1]);
+    /// ```
+    /// 
+    pub unsafe fn try_get_many_mut<const K : usize>(&self, indices : [usize; K]) -> Result<[&mut T; K], IndexError> {
+        let slice : &MultiRef<[T]> = self;
+        return slice.try_get_many_mut(indices);
+    }
+
+    /// Get mutable references to several distinct elements of the wrapped array, like `slice::get_disjoint_mut`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `indices` : The indices of the elements.
+    /// 
+    /// # Returns
+    /// 
+    /// Mutable references to the elements, in the order of `indices`,
+    /// or `None` if an index is out of range or appears more than once. See `try_get_many_mut()` to tell these apart.
+    /// 
+    /// # Safety
+    /// 
+    /// The same as `try_get_many_mut()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new([1, 2, 3]);
+    /// 
+    /// let [a, b] = unsafe {multiref.get_disjoint_mut([0, 2])}.unwrap();
+    /// *a += *b;
+    /// assert!(unsafe {multiref.get_disjoint_mut([3])}.is_none());
+    /// assert_eq!(multiref.unwrap(), [4, 2, 3]);
+    /// ```
+    /// 
+    pub unsafe fn get_disjoint_mut<const K : usize>(&self, indices : [usize; K]) -> Option<[&mut T; K]> {
+        return self.try_get_many_mut(indices).ok();
+    }
+
+    /// Overwrite an element of the wrapped array, but only if it currently equals `expected`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `index`    : The index of the element.
+    /// * `expected` : The value the element must have for it to be overwritten.
+    /// * `new`      : The value to write.
+    /// 
+    /// # Returns
+    /// 
+    /// `true` if the element was overwritten.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `index` is out of bounds.
+    /// 
+    /// # Safety
+    /// 
+    /// The element is mutated through a shared reference, the same as with `get_mut()`.
+    /// This is not atomic, and gives no guarantees across threads.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new([1, 2, 3]);
+    /// 
+    /// assert!(unsafe {multiref.compare_and_set_at(1, &2, 5)});
+    /// assert!(!unsafe {multiref.compare_and_set_at(2, &2, 5)});
+    /// assert_eq!(multiref.unwrap(), [1, 5, 3]);
+    /// ```
+    /// 
+    pub unsafe fn compare_and_set_at(&self, index : usize, expected : &T, new : T) -> bool where T : PartialEq {
+        let element = &mut self.get_mut()[index];
+        if *element != *expected {
+            return false;
+        }
+        *element = new;
+        return true;
+    }
+
+    /// Iterate over mutable references to the elements of the wrapped array, through a shared reference.
+    /// 
+    /// # Returns
+    /// 
+    /// An iterator over the elements.
+    /// 
+    /// # Safety
+    /// 
+    /// The yielded references alias every other reference obtained from this `MultiRef`, including ones
+    /// yielded by other iterators, so the same collection can be iterated in nested loops.
+    /// The iterator is invalidated if the array is replaced while it is alive (e.g. by `replace()`).
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new([1, 2, 3]);
+    /// 
+    /// for x in unsafe {multiref.iter_mut_aliased()} {
+    ///     *x *= 10;
+    /// }
+    /// assert_eq!(multiref.unwrap(), [10, 20, 30]);
+    /// ```
+    /// 
+    pub unsafe fn iter_mut_aliased(&self) -> slice::IterMut<'_, T> {
+        return self.get_mut().iter_mut();
+    }
+
+    /// Overwrite every element of the wrapped array with a value computed from its index.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `f` : Called with each index, in order, to produce the new element.
+    /// 
+    /// # Safety
+    /// 
+    /// The elements are mutated through a shared reference, the same as with `get_mut()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new([0; 4]);
+    /// 
+    /// unsafe {multiref.fill_with_index(|i| i * 2)};
+    /// assert_eq!(multiref.unwrap(), [0, 2, 4, 6]);
+    /// ```
+    /// 
+    pub unsafe fn fill_with_index(&self, mut f : impl FnMut(usize) -> T) {
+        for (i, element) in self.get_mut().iter_mut().enumerate() {
+            *element = f(i);
+        }
+    }
+
+    /// View the wrapped array as a slice of `Cell`s, giving safe `get`/`set` access to each element.
+    /// 
+    /// # Returns
+    /// 
+    /// A slice of `Cell`s backed by the wrapped array.
+    /// 
+    /// # Safety
+    /// 
+    /// Setting a cell mutates the wrapped array through a shared reference, the same as with `get_mut()`,
+    /// so the caller must not keep other references to the elements in use while doing so.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new([1, 2, 3]);
+    /// 
+    /// let cells = unsafe {multiref.as_slice_of_cells()};
+    /// cells[0].set(cells[2].get());
+    /// assert_eq!(multiref.unwrap(), [3, 2, 3]);
+    /// ```
+    /// 
+    pub unsafe fn as_slice_of_cells(&self) -> &[Cell<T>] {
+        return slice::from_raw_parts(self.0.get() as *const Cell<T>, N);
+    }
+
+    /// View the elements as a slice of `MultiRef`s, one per element. See `MultiRef::<[T]>::as_slice_of_refs()`.
+    /// 
+    /// # Returns
+    /// 
+    /// A slice of element cells, borrowed for as long as this `MultiRef`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new([1, 2, 3]);
+    /// 
+    /// let [a, b, c] = multiref.as_slice_of_refs() else {unreachable!()};
+    /// unsafe {a.swap(c)};
+    /// assert_eq!(unsafe {*b.get_ref()}, 2);
+    /// assert_eq!(multiref.unwrap(), [3, 2, 1]);
+    /// ```
+    /// 
+    pub fn as_slice_of_refs(&self) -> &[MultiRef<T>] {
+        let slice : &MultiRef<[T]> = self;
+        return slice.as_slice_of_refs();
+    }
+
+    /// Sum the elements on several threads. The array is split into disjoint chunks,
+    /// each summed on its own scoped thread, and the partial sums are added together on the calling thread.
+    /// 
+    /// The calling thread is blocked until every chunk is summed, and `MultiRef` is not `Sync`,
+    /// so nothing can write to the elements while the threads read them.
+    /// 
+    /// # Returns
+    /// 
+    /// The sum of the elements, or `T::default()` if the array is empty.
+    /// 
+    /// # Panics
+    /// 
+    /// If adding two values panics (e.g. on integer overflow in debug builds).
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new([1, 2, 3, 4, 5]);
+    /// 
+    /// assert_eq!(multiref.parallel_sum(), 15);
+    /// ```
+    /// 
+    pub fn parallel_sum(&self) -> T where T : Copy + Add<Output = T> + Default + Send + Sync {
+        let elements = unsafe {self.get_ref()};
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let chunk_size = N.div_ceil(threads).max(1);
+        return thread::scope(|scope| {
+            let partials = elements.chunks(chunk_size)
+                .map(|chunk| scope.spawn(|| chunk.iter().fold(T::default(), |sum, &element| sum + element)))
+                .collect::<Vec<_>>();
+            return partials.into_iter().fold(T::default(), |sum, partial| sum + partial.join().unwrap());
+        });
+    }
+
+}
+
+
+/// The reason a set of indices was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexError {
+    /// The index is out of bounds.
+    OutOfRange(usize),
+    /// The index was given more than once.
+    Duplicate(usize)
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            IndexError::OutOfRange(index) => write!(f, "index {} is out of range", index),
+            IndexError::Duplicate(index)  => write!(f, "index {} was given more than once", index)
+        };
+    }
+}
+
+impl Error for IndexError {}
+
+
+macro_rules! assign_ops {
+    ($($method:ident $op_trait:ident $op:tt),* $(,)?) => {
+        impl<T> MultiRef<T> {$(
+
+            #[doc = concat!("Apply `", stringify!($op), "` to the wrapped value through a shared reference.")]
+            /// 
+            /// # Arguments
+            /// 
+            /// * `rhs` : The right hand side of the operation.
+            /// 
+            /// # Safety
+            /// 
+            /// The wrapped value is mutated through a shared reference, the same as with `get_mut()`.
+            /// 
+            /// # Examples
+            /// 
+            /// ```
+            /// use pholib::MultiRef;
+            /// let multiref = MultiRef::new(12);
+            /// 
+            #[doc = concat!("unsafe {multiref.", stringify!($method), "(2)};")]
+            #[doc = concat!("assert_eq!(multiref.unwrap(), {let mut x = 12; x ", stringify!($op), " 2; x});")]
+            /// ```
+            /// 
+            pub unsafe fn $method<U>(&self, rhs : U) where T : $op_trait<U> {
+                *self.get_mut() $op rhs;
+            }
+
+        )*}
+    };
+}
+
+assign_ops!(
+    add_assign    AddAssign    +=,
+    sub_assign    SubAssign    -=,
+    mul_assign    MulAssign    *=,
+    div_assign    DivAssign    /=,
+    bitor_assign  BitOrAssign  |=,
+    bitand_assign BitAndAssign &=,
+    bitxor_assign BitXorAssign ^=,
+    shl_assign    ShlAssign    <<=,
+    shr_assign    ShrAssign    >>=
+);
+
+
+/// Create two `MultiRef`s that are meant to point at each other, e.g. two nodes of a graph.
+/// 
+/// The cells store their values inline, so any pointer into them is invalidated when they are moved.
+/// Wire the pointers up only after the returned cells have been bound to their final places,
+/// and don't move them while the pointers are in use.
+/// The two values live in separate storage, so mutating one through the other's pointer never aliases itself.
+/// 
+/// # Arguments
+/// 
+/// * `a` : The value of the first `MultiRef`.
+/// * `b` : The value of the second `MultiRef`.
+/// 
+/// # Returns
+/// 
+/// The two `MultiRef`s, not yet wired up.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::{MultiRef, pair_cyclic};
+/// use std::ptr;
+/// struct Node {
+///     pub value : i32,
+///     pub other : *mut Node
+/// }
+/// let (a, b) = pair_cyclic(
+///     Node {value : 1, other : ptr::null_mut()},
+///     Node {value : 2, other : ptr::null_mut()}
+/// );
+/// 
+/// unsafe {
+///     a.get_mut().other = b.get_mut();
+///     b.get_mut().other = a.get_mut();
+///     (*a.get_ref().other).value += 10;
+///     (*b.get_ref().other).value += 20;
+/// }
+/// assert_eq!(a.value, 21);
+/// assert_eq!(b.value, 12);
+/// ```
+/// 
+pub fn pair_cyclic<A, B>(a : A, b : B) -> (MultiRef<A>, MultiRef<B>) {
+    return (MultiRef::new(a), MultiRef::new(b));
+}
+
+/// Get a raw pointer to a named field of the value wrapped in a `MultiRef`.
+/// The offset is computed by the compiler with `addr_of_mut!`, so no reference to the whole value is created
+/// and no byte offsets have to be worked out by hand.
+/// 
+/// Taking the pointer is safe; writing or reading through it is `unsafe` with the same rules as `get_mut()`.
+/// 
+/// # Arguments
+/// 
+/// * `container` : A `&MultiRef<Type>`.
+/// * `Type` : The wrapped struct type.
+/// * `field` : The name of the field.
+/// 
+/// # Returns
+/// 
+/// A `*mut` pointer to the field inside the `MultiRef`'s storage.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::{MultiRef, multiref_field_ptr};
+/// struct Point {
+///     pub x : i32,
+///     pub y : i32
+/// }
+/// let point = MultiRef::new(Point {x : 1, y : 2});
+/// 
+/// let y = multiref_field_ptr!(&point, Point, y);
+/// unsafe {*y += 10};
+/// assert_eq!(point.y, 12);
+/// ```
+/// 
+#[macro_export]
+macro_rules! multiref_field_ptr {
+    ($container:expr, $ty:ty, $field:ident) => {{
+        let container : &$crate::MultiRef<$ty> = $container;
+        let ptr = container.as_mut_ptr();
+        #[allow(unused_unsafe)]
+        unsafe {::core::ptr::addr_of_mut!((*ptr).$field)}
+    }};
+}
+
+/// Merge two sorted wrapped `Vec`s into a new sorted `Vec`, without consuming either `MultiRef`.
+/// Equal elements from `a` come before those from `b`, so the merge is stable.
+/// 
+/// # Arguments
+/// 
+/// * `a` : The first sorted `Vec`.
+/// * `b` : The second sorted `Vec`.
+/// 
+/// # Returns
+/// 
+/// A new `Vec` holding clones of every element of `a` and `b`, in sorted order.
+/// If either input is not sorted, the output is unspecified but still holds every element.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::{MultiRef, merge_sorted};
+/// let a = MultiRef::new(vec![1, 4, 6]);
+/// let b = MultiRef::new(vec![2, 3, 7]);
+/// 
+/// assert_eq!(merge_sorted(&a, &b), vec![1, 2, 3, 4, 6, 7]);
+/// assert_eq!(a.len(), 3);
+/// ```
+/// 
+pub fn merge_sorted<T : Ord + Clone>(a : &MultiRef<Vec<T>>, b : &MultiRef<Vec<T>>) -> Vec<T> {
+    let (a, b) = unsafe {(a.get_ref(), b.get_ref())};
+    let mut merged = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if b[j] < a[i] {
+            merged.push(b[j].clone());
+            j += 1;
+        } else {
+            merged.push(a[i].clone());
+            i += 1;
+        }
+    }
+    merged.extend_from_slice(&a[i..]);
+    merged.extend_from_slice(&b[j..]);
+    return merged;
+}
+
+/// Copy the value of one `MultiRef` into another, but only if they differ.
+/// 
+/// # Arguments
+/// 
+/// * `a` : The `MultiRef` whose value is read.
+/// * `b` : The `MultiRef` whose value is overwritten.
+/// 
+/// # Returns
+/// 
+/// `true` if the values differed and `b` was overwritten with a clone of `a`'s value, `false` if `b` was left untouched.
+/// 
+/// # Safety
+/// 
+/// The value of `b` is overwritten through a shared reference, the same as with `set()`.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::{MultiRef, sync_if_different};
+/// let a = MultiRef::new(10);
+/// let b = MultiRef::new(5);
+/// 
+/// assert!(unsafe {sync_if_different(&a, &b)});
+/// assert!(!unsafe {sync_if_different(&a, &b)});
+/// assert_eq!(b.unwrap(), 10);
+/// ```
+/// 
+pub unsafe fn sync_if_different<T : PartialEq + Clone>(a : &MultiRef<T>, b : &MultiRef<T>) -> bool {
+    if a.get_ref() == b.get_ref() {
+        return false;
+    }
+    b.set(a.get_ref().clone());
+    return true;
+}
+
+
+impl<T : Copy> MultiRef<T> {
+
+    /// Get a copy of the wrapped value.
+    /// 
+    /// # Returns
+    /// 
+    /// A copy of the wrapped value.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(10);
+    /// 
+    /// unsafe {*multiref.get_mut() += 1};
+    /// assert_eq!(multiref.get(), 11);
+    /// ```
+    /// 
+    pub fn get(&self) -> T {
+        return unsafe {*self.get_ref()};
+    }
+
+    /// Update the wrapped value with a function of its current value, like `Cell::update`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `f` : Called with a copy of the wrapped value to produce the new value.
+    /// 
+    /// # Returns
+    /// 
+    /// The new value.
+    /// 
+    /// # Safety
+    /// 
+    /// The wrapped value is overwritten through a shared reference, the same as with `get_mut()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(10);
+    /// 
+    /// assert_eq!(unsafe {multiref.get_update(|n| n * 2)}, 20);
+    /// assert_eq!(multiref.get(), 20);
+    /// ```
+    /// 
+    pub unsafe fn get_update(&self, f : impl FnOnce(T) -> T) -> T {
+        let value = f(self.get());
+        *self.get_mut() = value;
+        return value;
+    }
+
+}
+
+
+impl<T : Clone> MultiRef<T> {
+
+    /// Clone the wrapped value, without consuming the `MultiRef`.
+    /// Can be used while mutable references are outstanding, capturing the value as it is at that moment.
+    /// Unlike cloning a container, this returns the bare `T` rather than a new `MultiRef`.
+    /// 
+    /// # Returns
+    /// 
+    /// A clone of the wrapped value.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(vec![1, 2]);
+    /// 
+    /// let mut snapshot = multiref.clone_inner();
+    /// snapshot.push(3);
+    /// unsafe {multiref.get_mut()}.push(4);
+    /// assert_eq!(snapshot, vec![1, 2, 3]);
+    /// assert_eq!(multiref.unwrap(), vec![1, 2, 4]);
+    /// ```
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(10);
+    /// 
+    /// let a = unsafe {multiref.get_mut()};
+    /// let snapshot = multiref.clone_inner();
+    /// *a += 1;
+    /// assert_eq!(snapshot, 10);
+    /// assert_eq!(multiref.unwrap(), 11);
+    /// ```
+    /// 
+    pub fn clone_inner(&self) -> T {
+        return unsafe {self.get_ref()}.clone();
+    }
+
+}
+
+
+impl MultiRef<Vec<u8>> {
+
+    /// Reinterpret the wrapped byte buffer as a mutable slice of `U`, without copying.
+    /// 
+    /// # Returns
+    /// 
+    /// A slice of `len / size_of::<U>()` elements backed by the buffer.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `U` is zero sized, if the length of the buffer is not a multiple of the size of `U`,
+    /// or if the buffer is not empty and not aligned for `U`.
+    /// 
+    /// # Safety
+    /// 
+    /// * `U` must be plain old data: every bit pattern of the right size must be a valid `U` (e.g. integers and floats, but not `bool`, `char` or references).
+    /// * The slice aliases every other reference obtained from this `MultiRef`, and dangles if the buffer is reallocated while it is in use.
+    /// * A `Vec<u8>` only guarantees an alignment of 1, so the alignment check can fail for any `U` with a larger alignment.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new(vec![0u8; 4]);
+    /// 
+    /// let words = unsafe {multiref.as_typed_slice::<u16>()};
+    /// words[1] = u16::from_ne_bytes([1, 2]);
+    /// assert_eq!(multiref.unwrap(), vec![0, 0, 1, 2]);
+    /// ```
+    /// 
+    pub unsafe fn as_typed_slice<U>(&self) -> &mut [U] {
+        let bytes = self.get_mut();
+        let size  = mem::size_of::<U>();
+        assert!(size != 0, "can not reinterpret bytes as a zero sized type");
+        if bytes.is_empty() {
+            // An empty `Vec` has a dangling pointer that need not be aligned for `U`.
+            return &mut [];
+        }
+        assert!(bytes.len() % size == 0, "buffer length {} is not a multiple of {}", bytes.len(), size);
+        assert!((bytes.as_ptr() as *const U).is_aligned(), "buffer is not aligned to {}", mem::align_of::<U>());
+        return slice::from_raw_parts_mut(bytes.as_mut_ptr() as *mut U, bytes.len() / size);
+    }
+
+}
+
+
+impl<K, V, S> MultiRef<HashMap<K, V, S>> {
+
+    /// Apply a function to every value of the wrapped map, in place.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `f` : Called with a mutable reference to each value, in the map's iteration order.
+    /// 
+    /// # Safety
+    /// 
+    /// The values are mutated through a shared reference, the same as with `get_mut()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// use std::collections::HashMap;
+    /// let multiref = MultiRef::new(HashMap::from([("a", 1)]));
+    /// 
+    /// unsafe {multiref.map_values(|v| *v += 1)};
+    /// assert_eq!(multiref["a"], 2);
+    /// ```
+    /// 
+    pub unsafe fn map_values(&self, f : impl FnMut(&mut V)) {
+        self.get_mut().values_mut().for_each(f);
+    }
+
+}
+
+
+impl<T, E> MultiRef<Result<T, E>> {
+
+    /// Return the wrapped `Result` and drop the `MultiRef`.
+    /// The same as `unwrap()`, but without the confusion of calling `unwrap()` on something holding a `Result`.
+    /// `is_ok()` and `is_err()` can be called on the `MultiRef` directly, through `Deref`.
+    /// 
+    /// # Returns
+    /// 
+    /// The wrapped `Result`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new("10".parse::<i32>());
+    /// 
+    /// assert!(multiref.is_ok());
+    /// assert_eq!(multiref.into_result(), Ok(10));
+    /// ```
+    /// 
+    pub fn into_result(self) -> Result<T, E> {
+        return self.unwrap();
+    }
+
+}
+
+
+impl<T> MultiRef<Option<T>> {
+
+    /// Replace the wrapped value, but only if it is currently `Some`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `value` : The new value to put in the `Some`.
+    /// 
+    /// # Returns
+    /// 
+    /// The old value if there was one, otherwise `None` and the `MultiRef` is left untouched.
+    /// 
+    /// # Safety
+    /// 
+    /// The same as `replace()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let some = MultiRef::new(Some(1));
+    /// let none = MultiRef::new(None);
+    /// 
+    /// assert_eq!(unsafe {some.replace_if_some(2)}, Some(1));
+    /// assert_eq!(unsafe {none.replace_if_some(2)}, None);
+    /// assert_eq!(some.unwrap(), Some(2));
+    /// assert_eq!(none.unwrap(), None);
+    /// ```
+    /// 
+    pub unsafe fn replace_if_some(&self, value : T) -> Option<T> {
+        return self.get_mut().as_mut().map(|old| mem::replace(old, value));
+    }
+
+    /// Get a mutable reference to the value in the slot, inserting the result of `f` first if the slot is empty.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `f` : Called to create the value, only if the slot is empty.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable reference to the value in the slot.
+    /// 
+    /// # Safety
+    /// 
+    /// The returned reference aliases every other reference obtained from this `MultiRef`, the same as with `get_mut()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let slot = MultiRef::new(None);
+    /// 
+    /// *unsafe {slot.get_or_insert_with(|| 1)} += 1;
+    /// *unsafe {slot.get_or_insert_with(|| 10)} += 1;
+    /// assert_eq!(slot.unwrap(), Some(3));
+    /// ```
+    /// 
+    pub unsafe fn get_or_insert_with(&self, f : impl FnOnce() -> T) -> &mut T {
+        return self.get_mut().get_or_insert_with(f);
+    }
+
+    /// Put a value in the slot, dropping the old one if there was one.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `value` : The value to put in the slot.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable reference to the value in the slot.
+    /// 
+    /// # Safety
+    /// 
+    /// The same as `set()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let slot = MultiRef::new(Some(1));
+    /// 
+    /// *unsafe {slot.insert(5)} += 1;
+    /// assert_eq!(slot.unwrap(), Some(6));
+    /// ```
+    /// 
+    pub unsafe fn insert(&self, value : T) -> &mut T {
+        return self.get_mut().insert(value);
+    }
+
+    /// Get an immutable reference to the value in the slot.
+    /// 
+    /// # Returns
+    /// 
+    /// A reference to the value in the slot, or `None` if it is empty.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let slot = MultiRef::new(Some(1));
+    /// 
+    /// assert_eq!(slot.as_inner_ref(), Some(&1));
+    /// ```
+    /// 
+    pub fn as_inner_ref(&self) -> Option<&T> {
+        return unsafe {self.get_ref()}.as_ref();
+    }
+
+    /// Get a mutable reference to the value in the slot.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable reference to the value in the slot, or `None` if it is empty.
+    /// 
+    /// # Safety
+    /// 
+    /// The returned reference aliases every other reference obtained from this `MultiRef`, the same as with `get_mut()`.
+    /// It dangles if the slot is emptied or replaced while it is in use.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let slot = MultiRef::new(Some(1));
+    /// 
+    /// if let Some(value) = unsafe {slot.as_inner_mut()} {
+    ///     *value += 1;
+    /// }
+    /// assert_eq!(slot.unwrap(), Some(2));
+    /// ```
+    /// 
+    pub unsafe fn as_inner_mut(&self) -> Option<&mut T> {
+        return self.get_mut().as_mut();
+    }
+
+    /// Take the value out of the slot, leaving it empty.
+    /// 
+    /// # Returns
+    /// 
+    /// The value that was in the slot, or `None` if it was already empty.
+    /// 
+    /// # Safety
+    /// 
+    /// The same as `take()`. References obtained with `as_inner_ref()` or `as_inner_mut()` dangle afterwards.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let slot = MultiRef::new(Some(1));
+    /// 
+    /// assert_eq!(unsafe {slot.take_inner()}, Some(1));
+    /// assert_eq!(unsafe {slot.take_inner()}, None);
+    /// ```
+    /// 
+    pub unsafe fn take_inner(&self) -> Option<T> {
+        return self.get_mut().take();
+    }
+
+    /// Check whether the slot holds a value.
+    /// 
+    /// # Returns
+    /// 
+    /// `true` if the slot is filled.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let slot = MultiRef::new(None);
+    /// 
+    /// assert!(!slot.is_some());
+    /// unsafe {slot.insert(1)};
+    /// assert!(slot.is_some());
+    /// ```
+    /// 
+    pub fn is_some(&self) -> bool {
+        return unsafe {self.get_ref()}.is_some();
+    }
+
+}
+
+
+impl<T> MultiRef<MaybeUninit<T>> {
+
+    /// Initialise the wrapped value.
+    /// If it was already initialised, the old value is overwritten without being dropped.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `value` : The value to write.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable reference to the now initialised value.
+    /// 
+    /// # Safety
+    /// 
+    /// The value is written through a shared reference, the same as with `get_mut()`.
+    /// A written value is only dropped once `assume_init()` has been called. Dropping the `MultiRef` before that leaks it.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::<String>::new_uninit();
+    /// 
+    /// unsafe {multiref.write(String::from("a"))}.push('b');
+    /// assert_eq!(unsafe {multiref.assume_init_ref()}, "ab");
+    /// unsafe {multiref.assume_init()};
+    /// ```
+    /// 
+    pub unsafe fn write(&self, value : T) -> &mut T {
+        return self.get_mut().write(value);
+    }
+
+    /// Mark the wrapped value as initialised.
+    /// 
+    /// # Returns
+    /// 
+    /// A `MultiRef` wrapping the initialised value, which is dropped normally from now on.
+    /// 
+    /// # Safety
+    /// 
+    /// The wrapped value must have been initialised, with `write()` or through a raw pointer.
+    /// The value is moved into the returned `MultiRef`, so pointers taken into this one do not point to it any more.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::<Vec<i32>>::new_uninit();
+    /// 
+    /// unsafe {multiref.write(vec![1, 2])};
+    /// let multiref = unsafe {multiref.assume_init()};
+    /// assert_eq!(multiref.unwrap(), vec![1, 2]);
+    /// ```
+    /// 
+    pub unsafe fn assume_init(self) -> MultiRef<T> {
+        return MultiRef::new(self.unwrap().assume_init());
+    }
+
+    /// Get an immutable reference to the wrapped value, assuming it is initialised.
+    /// 
+    /// # Returns
+    /// 
+    /// An immutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The wrapped value must have been initialised. Otherwise the same as `get_ref()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::<i32>::new_uninit();
+    /// 
+    /// unsafe {multiref.write(10)};
+    /// assert_eq!(unsafe {*multiref.assume_init_ref()}, 10);
+    /// ```
+    /// 
+    pub unsafe fn assume_init_ref(&self) -> &T {
+        return self.get_ref().assume_init_ref();
+    }
+
+    /// Get a mutable reference to the wrapped value, assuming it is initialised.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The wrapped value must have been initialised. Otherwise the same as `get_mut()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::<i32>::new_uninit();
+    /// 
+    /// unsafe {multiref.write(10)};
+    /// unsafe {*multiref.assume_init_mut() += 1};
+    /// assert_eq!(unsafe {multiref.assume_init()}.unwrap(), 11);
+    /// ```
+    /// 
+    pub unsafe fn assume_init_mut(&self) -> &mut T {
+        return self.get_mut().assume_init_mut();
+    }
+
+}
+
+
+impl MultiRef<String> {
+
+    /// Borrow the wrapped string as a `Cow`, for APIs that take `Cow<str>`.
+    /// 
+    /// # Returns
+    /// 
+    /// A borrowed `Cow` over the wrapped string. Call `into_owned()` on it to keep a copy that is independent of the `MultiRef`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// use std::borrow::Cow;
+    /// let multiref = MultiRef::new(String::from("name"));
+    /// 
+    /// assert_eq!(multiref.as_cow_str(), Cow::Borrowed("name"));
+    /// ```
+    /// 
+    pub fn as_cow_str(&self) -> Cow<'_, str> {
+        return Cow::Borrowed(unsafe {self.get_ref()});
+    }
+
+}
+
+
+impl<T> MultiRef<[T]> {
+
+    /// View the wrapped slice as a slice of `MultiRef`s, one per element, like `Cell::as_slice_of_cells`.
+    /// Each element cell aliases the corresponding element of this `MultiRef`, so they can be handed out separately.
+    /// 
+    /// # Returns
+    /// 
+    /// A slice of element cells, borrowed for as long as this `MultiRef`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new([1, 2, 3]);
+    /// let slice : &MultiRef<[i32]> = &multiref;
+    /// 
+    /// let cells = slice.as_slice_of_refs();
+    /// unsafe {*cells[0].get_mut() += *cells[2].get_ref()};
+    /// assert_eq!(multiref.unwrap(), [4, 2, 3]);
+    /// ```
+    /// 
+    pub fn as_slice_of_refs(&self) -> &[MultiRef<T>] {
+        // `MultiRef<[T]>` has the same layout as `[T]`, and `MultiRef<T>` as `T`.
+        return unsafe {&*(self.as_ptr() as *const [MultiRef<T>])};
+    }
+
+    /// Get mutable references to several distinct elements of the wrapped slice.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `indices` : The indices of the elements.
+    /// 
+    /// # Returns
+    /// 
+    /// Mutable references to the elements, in the order of `indices`.
+    /// 
+    /// # Errors
+    /// 
+    /// * `IndexError::OutOfRange` if an index is not less than the length of the slice.
+    /// * `IndexError::Duplicate` if an index appears more than once.
+    /// 
+    /// # Safety
+    /// 
+    /// The returned references do not overlap each other, but they alias every other reference obtained from this `MultiRef`.
+    /// The caller is responsible for preventing data races and any other undefined behaviour this can cause.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::{MultiRef, IndexError};
+    /// let multiref = MultiRef::new([1, 2, 3]);
+    /// let slice : &MultiRef<[i32]> = &multiref;
+    /// 
+    /// let [a, b] = unsafe {slice.try_get_many_mut([2, 0])}.unwrap();
+    /// std::mem::swap(a, b);
+    /// assert_eq!(unsafe {slice.try_get_many_mut([3])}.err(), Some(IndexError::OutOfRange(3)));
+    /// assert_eq!(multiref.unwrap(), [3, 2, 1]);
+    /// ```
+    /// 
+    pub unsafe fn try_get_many_mut<const K : usize>(&self, indices : [usize; K]) -> Result<[&mut T; K], IndexError> {
+        let len = self.as_ptr().len();
+        for (i, &index) in indices.iter().enumerate() {
+            if index >= len {
+                return Err(IndexError::OutOfRange(index));
+            }
+            if indices[.. i].contains(&index) {
+                return Err(IndexError::Duplicate(index));
+            }
+        }
+        let elements = self.as_mut_ptr() as *mut T;
+        return Ok(indices.map(|index| &mut *elements.add(index)));
+    }
+
+    /// Get mutable references to several distinct elements of the wrapped slice, like `slice::get_disjoint_mut`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `indices` : The indices of the elements.
+    /// 
+    /// # Returns
+    /// 
+    /// Mutable references to the elements, in the order of `indices`,
+    /// or `None` if an index is out of range or appears more than once. See `try_get_many_mut()` to tell these apart.
+    /// 
+    /// # Safety
+    /// 
+    /// The same as `try_get_many_mut()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiRef;
+    /// let multiref = MultiRef::new([1, 2, 3]);
+    /// let slice : &MultiRef<[i32]> = &multiref;
+    /// 
+    /// let [a, b] = unsafe {slice.get_disjoint_mut([1, 2])}.unwrap();
+    /// *b += *a;
+    /// assert!(unsafe {slice.get_disjoint_mut([1, 1])}.is_none());
+    /// assert_eq!(multiref.unwrap(), [1, 2, 5]);
+    /// ```
+    /// 
+    pub unsafe fn get_disjoint_mut<const K : usize>(&self, indices : [usize; K]) -> Option<[&mut T; K]> {
+        return self.try_get_many_mut(indices).ok();
+    }
+
+}
+
+
+/// Hashes the wrapped value.
+/// 
+/// Only available with the `hash` feature, so using a `MultiRef` as a hash key is always a deliberate choice.
+/// 
+/// # Warning
+/// 
+/// * The wrapped value can be mutated through other references while the `MultiRef` is used as a key.
+/// * If that happens, the hash drifts away from the one the map stored and the map becomes corrupted.
+/// * Only use a `MultiRef` as a hash key if you are sure that no mutable reference will be used while it is in the map.
+/// * Clippy's `mutable_key_type` lint rejects this by default, so it has to be allowed explicitly where it is done on purpose.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// use std::collections::HashMap;
+/// let mut map = HashMap::new();
+/// map.insert(MultiRef::new(String::from("key")), 10);
+/// 
+/// assert_eq!(map.get(&MultiRef::new(String::from("key"))), Some(&10));
+/// ```
+/// 
+#[cfg(feature = "hash")]
+impl<T : Hash + ?Sized> Hash for MultiRef<T> {
+    fn hash<H : Hasher>(&self, state : &mut H) {
+        unsafe {self.get_ref()}.hash(state);
+    }
+}
+
+/// Compares the wrapped values.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// assert!(MultiRef::new(10) == MultiRef::new(10));
+/// assert!(MultiRef::new(10) != MultiRef::new(11));
+/// ```
+/// 
+impl<T : PartialEq + ?Sized> PartialEq for MultiRef<T> {
+    fn eq(&self, other : &MultiRef<T>) -> bool {
+        return unsafe {self.get_ref() == other.get_ref()};
+    }
+}
+
+impl<T : Eq + ?Sized> Eq for MultiRef<T> {}
+
+
+
+/// Borrows the wrapped value, so a `MultiRef` can be passed to functions taking `impl AsRef<T>`.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// fn length(value : impl AsRef<String>) -> usize {
+///     return value.as_ref().len();
+/// }
+/// let multiref = MultiRef::new(String::from("abc"));
+/// 
+/// assert_eq!(length(&multiref), 3);
+/// ```
+/// 
+impl<T : ?Sized> AsRef<T> for MultiRef<T> {
+    fn as_ref(&self) -> &T {
+        return unsafe {self.get_ref()};
+    }
+}
+
+/// Mutably borrows the wrapped value.
+/// 
+/// Unlike `get_mut()`, the trait requires `&mut self`, so the borrow checker guarantees that no other reference exists.
+/// To mutate through a shared `&MultiRef`, use `get_mut()`.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// fn push(mut value : impl AsMut<Vec<i32>>) {
+///     value.as_mut().push(1);
+/// }
+/// let mut multiref = MultiRef::new(Vec::new());
+/// 
+/// push(&mut multiref);
+/// assert_eq!(multiref.unwrap(), vec![1]);
+/// ```
+/// 
+impl<T : ?Sized> AsMut<T> for MultiRef<T> {
+    fn as_mut(&mut self) -> &mut T {
+        return unsafe {self.get_mut()};
+    }
+}
+
+/// Borrows the wrapped value.
+/// 
+/// # Warning
+/// 
+/// * `Borrow` expects the borrowed value to hash and compare the same as the owner.
+/// * This only holds as long as the wrapped value is not mutated through another reference,
+///   so these semantics are intentionally broken by anything that uses `get_mut()`.
+/// 
+impl<T : ?Sized> Borrow<T> for MultiRef<T> {
+    fn borrow(&self) -> &T {
+        return unsafe {self.get_ref()};
+    }
+}
+
+/// Borrows the wrapped string as a `str`, so it can be passed to functions taking `impl Borrow<str>`.
+/// With the `hash` feature, this also lets maps keyed on `MultiRef<String>` be looked up with a `&str`.
+/// 
+/// # Warning
+/// 
+/// * This intentionally breaks the usual `Borrow` invariants as soon as the string is mutated through another reference.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// use std::borrow::Borrow;
+/// fn shout(value : impl Borrow<str>) -> String {
+///     return value.borrow().to_uppercase();
+/// }
+/// let multiref = MultiRef::new(String::from("key"));
+/// 
+/// assert_eq!(shout(multiref), "KEY");
+/// ```
+/// 
+impl Borrow<str> for MultiRef<String> {
+    fn borrow(&self) -> &str {
+        return unsafe {self.get_ref()};
+    }
+}
+
+/// Mutably borrows the wrapped value.
+/// 
+/// Like `AsMut`, the trait requires `&mut self`. To mutate through a shared `&MultiRef`, use `get_mut()`.
+/// 
+impl<T : ?Sized> BorrowMut<T> for MultiRef<T> {
+    fn borrow_mut(&mut self) -> &mut T {
+        return unsafe {self.get_mut()};
+    }
+}
+
+
+
+/// Wrap a value in a new `MultiRef`, the same as `MultiRef::new`.
+/// 
+/// Creating a `MultiRef` can not cause undefined behaviour by itself, so the conversion is safe.
+/// The danger only starts once references are taken with `get_ref()` and `get_mut()`, which stay `unsafe`.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// let multiref : MultiRef<i32> = 10.into();
+/// 
+/// assert_eq!(multiref.unwrap(), 10);
+/// ```
+/// 
+impl<T> From<T> for MultiRef<T> {
+    fn from(object : T) -> MultiRef<T> {
+        return MultiRef::new(object);
+    }
+}
+
+/// Wrap `T::default()`, the same as `new_default()`.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// let multiref : MultiRef<String> = Default::default();
+/// 
+/// assert_eq!(multiref.unwrap(), "");
+/// ```
+/// 
+impl<T : Default> Default for MultiRef<T> {
+    fn default() -> MultiRef<T> {
+        return MultiRef::new_default();
+    }
+}
+
+
+
+/// Dereferences to the wrapped value, so fields and methods of `T` can be used directly on the `MultiRef`.
+/// 
+/// Mutation through a shared `&MultiRef` still has to go through `get_mut()`,
+/// which makes the places where aliasing mutable references are created easy to find.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// struct Counter {
+///     pub count : i32
+/// }
+/// impl Counter {
+///     fn doubled(&self) -> i32 {
+///         return self.count * 2;
+///     }
+/// }
+/// let multiref = MultiRef::new(Counter {count : 5});
+/// 
+/// assert_eq!(multiref.count, 5);
+/// assert_eq!(multiref.doubled(), 10);
+/// 
+/// unsafe {multiref.get_mut()}.count += 1;
+/// assert_eq!(multiref.doubled(), 12);
+/// ```
+/// 
+impl<T : ?Sized> Deref for MultiRef<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        return unsafe {self.get_ref()};
+    }
+}
+
+/// Mutably dereferences to the wrapped value.
+/// 
+/// The trait requires `&mut self`, so this is only available while the `MultiRef` is borrowed exclusively.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// let mut multiref = MultiRef::new(vec![1, 2]);
+/// 
+/// multiref.push(3);
+/// assert_eq!(multiref.len(), 3);
+/// ```
+/// 
+impl<T : ?Sized> DerefMut for MultiRef<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        return unsafe {self.get_mut()};
+    }
+}
+
+
+
+/// Serializes the wrapped value transparently, the same way `Cell` does.
+/// 
+/// Only available with the `serde` feature.
+/// 
+#[cfg(feature = "serde")]
+impl<T : serde::Serialize> serde::Serialize for MultiRef<T> {
+    fn serialize<S : serde::Serializer>(&self, serializer : S) -> Result<S::Ok, S::Error> {
+        return unsafe {self.get_ref()}.serialize(serializer);
+    }
+}
+
+/// Deserializes a value and wraps it in a new `MultiRef`.
+/// 
+/// Only available with the `serde` feature.
+/// 
+#[cfg(feature = "serde")]
+impl<'de, T : serde::Deserialize<'de>> serde::Deserialize<'de> for MultiRef<T> {
+    fn deserialize<D : serde::Deserializer<'de>>(deserializer : D) -> Result<MultiRef<T>, D::Error> {
+        return T::deserialize(deserializer).map(MultiRef::new);
+    }
+}
+
+
+
+/// Indexes into the wrapped value.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// let multiref = MultiRef::new(vec![1, 2, 3]);
+/// 
+/// assert_eq!(multiref[1], 2);
+/// ```
+/// 
+impl<T : Index<I> + ?Sized, I> Index<I> for MultiRef<T> {
+    type Output = T::Output;
+    fn index(&self, index : I) -> &T::Output {
+        return &unsafe {self.get_ref()}[index];
+    }
+}
+
+/// Mutably indexes into the wrapped value.
+/// 
+/// The trait requires `&mut self`, so only one indexed mutation can be alive at a time.
+/// Index into `get_mut()` to hold several mutable references at once.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// let mut multiref = MultiRef::new(vec![1, 2, 3]);
+/// 
+/// multiref[1] = 9;
+/// assert_eq!(multiref.unwrap(), vec![1, 9, 3]);
+/// ```
+/// 
+impl<T : IndexMut<I> + ?Sized, I> IndexMut<I> for MultiRef<T> {
+    fn index_mut(&mut self, index : I) -> &mut T::Output {
+        return &mut unsafe {self.get_mut()}[index];
+    }
+}
+
+
+
+/// Formats the address of the wrapped value.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// let multiref = MultiRef::new(10);
+/// 
+/// assert_eq!(format!("{:p}", multiref), format!("{:#x}", multiref.addr()));
+/// ```
+/// 
+impl<T : ?Sized> fmt::Pointer for MultiRef<T> {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        return fmt::Pointer::fmt(&self.0.get(), f);
+    }
+}
+
+
+
+/// Iterates over a reference to the wrapped value.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// let multiref = MultiRef::new(vec![1, 2, 3]);
+/// 
+/// let mut sum = 0;
+/// for x in &multiref {
+///     sum += x;
+/// }
+/// assert_eq!(sum, 6);
+/// ```
+/// 
+impl<'l, T> IntoIterator for &'l MultiRef<T> where &'l T : IntoIterator {
+    type Item     = <&'l T as IntoIterator>::Item;
+    type IntoIter = <&'l T as IntoIterator>::IntoIter;
+    fn into_iter(self) -> Self::IntoIter {
+        return unsafe {self.get_ref()}.into_iter();
+    }
+}
+
+/// Iterates over a mutable reference to the wrapped value.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// let mut multiref = MultiRef::new(vec![1, 2, 3]);
+/// 
+/// for x in &mut multiref {
+///     *x *= 2;
+/// }
+/// assert_eq!(multiref.unwrap(), vec![2, 4, 6]);
+/// ```
+/// 
+impl<'l, T> IntoIterator for &'l mut MultiRef<T> where &'l mut T : IntoIterator {
+    type Item     = <&'l mut T as IntoIterator>::Item;
+    type IntoIter = <&'l mut T as IntoIterator>::IntoIter;
+    fn into_iter(self) -> Self::IntoIter {
+        return unsafe {self.get_mut()}.into_iter();
+    }
+}
+
+
+
+/// Collects an iterator into a new `MultiRef`.
+/// 
+/// Like `From`, this is safe because creating a `MultiRef` can not cause undefined behaviour by itself.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// let multiref : MultiRef<Vec<i32>> = (0..5).collect();
+/// 
+/// assert_eq!(multiref.unwrap(), vec![0, 1, 2, 3, 4]);
+/// ```
+/// 
+impl<A, T : FromIterator<A>> FromIterator<A> for MultiRef<T> {
+    fn from_iter<I : IntoIterator<Item = A>>(iter : I) -> MultiRef<T> {
+        return MultiRef::new(T::from_iter(iter));
+    }
+}
+
+/// Extends the wrapped collection.
+/// 
+/// The trait requires `&mut self`. To extend through a shared `&MultiRef`, use `extend_shared()`.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// let mut multiref = MultiRef::new(vec![1]);
+/// 
+/// multiref.extend([2, 3]);
+/// assert_eq!(multiref.unwrap(), vec![1, 2, 3]);
+/// ```
+/// 
+impl<A, T : Extend<A>> Extend<A> for MultiRef<T> {
+    fn extend<I : IntoIterator<Item = A>>(&mut self, iter : I) {
+        unsafe {self.get_mut()}.extend(iter);
+    }
+}
+
+
+
+/// Unwrap the value and turn it into a future, so a `MultiRef` can be `.await`ed once the aliasing phase is over.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiRef;
+/// use std::future::Future;
+/// use std::task::{Context, Poll, Waker};
+/// let multiref = MultiRef::new(async {10});
+/// 
+/// let mut future = Box::pin(async {multiref.await + 1});
+/// let poll = future.as_mut().poll(&mut Context::from_waker(Waker::noop()));
+/// assert_eq!(poll, Poll::Ready(11));
+/// ```
+/// 
+impl<T : IntoFuture> IntoFuture for MultiRef<T> {
+    type Output     = T::Output;
+    type IntoFuture = T::IntoFuture;
+    fn into_future(self) -> T::IntoFuture {
+        return self.unwrap().into_future();
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+#[allow(clippy::explicit_auto_deref, clippy::bool_assert_comparison)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+    use std::future::Future;
+    use std::marker::PhantomPinned;
+    use std::task::{Context, Poll, Waker};
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[test]
+    fn multiref() {unsafe {
+        let multiref = MultiRef::new(10);
+
+        let a = multiref.get_mut();
+        let b = multiref.get_mut();
+        *a += 1;
+        *b += 2;
+        assert_eq!(multiref.unwrap(), 13);
+    }}
+
+    #[test]
+    fn immut_and_mut() {unsafe {
+        let multiref = MultiRef::new(10);
+
+        let i = multiref.get_ref();
+        assert_eq!(*i, 10);
+
+        let a = multiref.get_mut();
+        let b = multiref.get_mut();
+        *a += 1;
+        *b += 2;
+        assert_eq!(*i, 13);
+        assert_eq!(multiref.unwrap(), 13);
+    }}
+
+    struct Test {
+        pub a : i32,
+        pub b : bool
+    }
+    
+    #[test]
+    fn struct_mut() {unsafe {
+        let multiref = MultiRef::new(
+            Test {
+                a : 1,
+                b : false
+            }
+        );
+
+        let x = multiref.get_mut();
+        let y = multiref.get_mut();
+        let z = multiref.get_mut();
+        (*x).a += 10;
+        (*y).b = true;
+        (*z).a += 7;
+    
+        let unwrapped = multiref.unwrap();
+        assert_eq!(unwrapped.a, 18);
+        assert_eq!(unwrapped.b, true);
+    }}
+    
+    #[test]
+    fn struct_immut_and_mut() {unsafe {
+        let multiref = MultiRef::new(
+            Test {
+                a : 1,
+                b : false
+            }
+        );
+
+        let i = multiref.get_ref();
+        assert_eq!((*i).a, 1);
+        assert_eq!((*i).b, false);
+
+        let x = multiref.get_mut();
+        let y = multiref.get_mut();
+        let z = multiref.get_mut();
+        (*x).a += 10;
+        (*y).b = true;
+        (*z).a += 7;
+        assert_eq!((*i).a, 18);
+        assert_eq!((*i).b, true);
+
+        let unwrapped = multiref.unwrap();
+        assert_eq!(unwrapped.a, 18);
+        assert_eq!(unwrapped.b, true);
+    }}
+
+    // THIS IS, FOR THE MOST PART, A TERRIBLE IDEA. IF YOU DO THIS, MAKE SURE YOU KNOW WHAT YOU'RE DOING.
+    #[test]
+    fn threads() {unsafe {
+        let a = 10;
+        let b = 10;
+        let c = 100;
+        let d = 1;
+
+        let multiref = MultiRef::new(a);
+
+        for _ in 0..b {
+            thread::scope(|scope| {
+                let mutref = multiref.get_mut();
+                scope.spawn(|| {
+                    for _ in 0..c {
+                        *mutref += d;
+                    }
+                });
+            });
+        }
+
+        assert_eq!(multiref.unwrap(), a + b * c * d);
+    }}
+
+    #[cfg(feature = "hash")]
+    #[test]
+    #[allow(clippy::mutable_key_type)]
+    fn hash_key() {unsafe {
+        let mut map = HashMap::new();
+        map.insert(MultiRef::new(String::from("a")), 1);
+        map.insert(MultiRef::new(String::from("b")), 2);
+
+        assert_eq!(map.get(&MultiRef::new(String::from("a"))), Some(&1));
+        assert_eq!(map.get(&MultiRef::new(String::from("b"))), Some(&2));
+        assert_eq!(map.get(&MultiRef::new(String::from("c"))), None);
+
+        let (key, _) = map.iter().find(|(_, value)| **value == 2).unwrap();
+        assert_eq!(key.get_ref(), "b");
+    }}
+
+    #[test]
+    fn take() {unsafe {
+        let multiref = MultiRef::new(String::from("old"));
+
+        let i = multiref.get_ref();
+        assert_eq!(multiref.take(), "old");
+        assert_eq!(*i, "");
+        assert_eq!(multiref.unwrap(), "");
+    }}
+
+    fn read_as_ref(value : impl AsRef<Test>) -> i32 {
+        return value.as_ref().a;
+    }
+
+    fn write_as_mut(mut value : impl AsMut<Test>) {
+        value.as_mut().b = true;
+    }
+
+    fn read_borrow<B : Borrow<Test>>(value : &B) -> i32 {
+        return value.borrow().a;
+    }
+
+    fn write_borrow_mut<B : BorrowMut<Test>>(value : &mut B) {
+        value.borrow_mut().a += 1;
+    }
+
+    #[test]
+    fn as_ref_and_borrow() {unsafe {
+        let mut multiref = MultiRef::new(
+            Test {
+                a : 1,
+                b : false
+            }
+        );
+
+        assert_eq!(read_as_ref(&multiref), 1);
+        assert_eq!(read_borrow(&multiref), 1);
+
+        write_as_mut(&mut multiref);
+        write_borrow_mut(&mut multiref);
+        assert_eq!(multiref.get_ref().a, 2);
+        assert_eq!(multiref.get_ref().b, true);
+
+        assert_eq!(read_as_ref(multiref), 2);
+    }}
+
+    #[test]
+    fn from() {unsafe {
+        let a : MultiRef<i32> = 10.into();
+        let b = MultiRef::from(String::from("abc"));
+
+        *a.get_mut() += 1;
+        b.get_mut().push('d');
+        assert_eq!(a.unwrap(), 11);
+        assert_eq!(b.unwrap(), "abcd");
+    }}
+
+    fn takes(x : impl AsRef<i32>) -> i32 {
+        return *x.as_ref();
+    }
+
+    fn takes_mut(mut x : impl AsMut<i32>) {
+        *x.as_mut() += 1;
+    }
+
+    #[test]
+    fn as_ref_generic() {unsafe {
+        let mut multiref = MultiRef::new(10);
+
+        let i = multiref.get_ref();
+        assert_eq!(takes(&multiref), 10);
         assert_eq!(*i, 10);
 
-        let a = multiref.get_mut();
-        let b = multiref.get_mut();
-        *a += 1;
-        *b += 2;
-        assert_eq!(*i, 13);
-        assert_eq!(multiref.unwrap(), 13);
+        takes_mut(&mut multiref);
+        assert_eq!(takes(&multiref), 11);
+        assert_eq!(takes(multiref), 11);
+    }}
+
+    #[test]
+    fn deref() {unsafe {
+        let mut multiref = MultiRef::new(
+            Test {
+                a : 1,
+                b : false
+            }
+        );
+
+        let x = multiref.get_mut();
+        x.a += 1;
+        assert_eq!(multiref.a, 2);
+        assert_eq!(multiref.b, false);
+
+        multiref.b = true;
+        assert_eq!(multiref.get_ref().b, true);
+    }}
+
+    #[test]
+    fn three_mut() {unsafe {
+        let multiref = MultiRef::new((1, 2.0, vec![3]));
+
+        let (a, b, c) = multiref.three_mut();
+        *a += 10;
+        *b *= 0.5;
+        c.push(4);
+        *a += 1;
+        assert_eq!(multiref.unwrap(), (12, 1.0, vec![3, 4]));
+    }}
+
+    fn borrowed_len<B : Borrow<str>>(value : &B) -> usize {
+        return value.borrow().len();
+    }
+
+    #[test]
+    #[allow(clippy::mutable_key_type)]
+    fn borrow_str() {unsafe {
+        let multiref = MultiRef::new(String::from("abc"));
+
+        assert_eq!(borrowed_len(&multiref), 3);
+        multiref.get_mut().push('d');
+        assert_eq!(borrowed_len(&multiref), 4);
+
+        #[cfg(feature = "hash")]
+        {
+            let mut map = HashMap::new();
+            map.insert(multiref, 1);
+            assert_eq!(map.get("abcd"), Some(&1));
+            assert_eq!(map.get("abc"), None);
+        }
+    }}
+
+    #[cfg(feature = "serde")]
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct SaveState {
+        pub score  : MultiRef<u32>,
+        pub items  : MultiRef<Vec<String>>,
+        pub nested : MultiRef<MultiRef<(i32, bool)>>
+    }
+
+    #[cfg(feature = "serde")]
+    fn save_state() -> SaveState {
+        return SaveState {
+            score  : MultiRef::new(10),
+            items  : MultiRef::new(vec![String::from("a"), String::from("b")]),
+            nested : MultiRef::new(MultiRef::new((-3, true)))
+        };
+    }
+
+    #[cfg(feature = "serde")]
+    fn assert_save_state(state : SaveState) {
+        assert_eq!(state.score.unwrap(), 11);
+        assert_eq!(state.items.unwrap(), vec![String::from("a"), String::from("b")]);
+        assert_eq!(state.nested.unwrap().unwrap(), (-3, true));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_json_round_trip() {unsafe {
+        let state = save_state();
+        *state.score.get_mut() += 1;
+
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(json, r#"{"score":11,"items":["a","b"],"nested":[-3,true]}"#);
+        assert_save_state(serde_json::from_str(&json).unwrap());
+    }}
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_bincode_round_trip() {unsafe {
+        let state = save_state();
+        *state.score.get_mut() += 1;
+
+        let bytes = bincode::serialize(&state).unwrap();
+        assert_eq!(bytes, bincode::serialize(&(11u32, vec!["a", "b"], (-3, true))).unwrap());
+        assert_save_state(bincode::deserialize(&bytes).unwrap());
+    }}
+
+    #[test]
+    fn move_to_back() {unsafe {
+        let multiref = MultiRef::new(vec!['a', 'b', 'c', 'd', 'e']);
+
+        let i = multiref.get_ref();
+        multiref.move_to_back(2);
+        assert_eq!(*i, vec!['a', 'b', 'd', 'e', 'c']);
+        multiref.move_to_back(4);
+        assert_eq!(*i, vec!['a', 'b', 'd', 'e', 'c']);
+        multiref.move_to_back(0);
+        assert_eq!(multiref.unwrap(), vec!['b', 'd', 'e', 'c', 'a']);
+    }}
+
+    #[test]
+    fn index() {unsafe {
+        let mut vec = MultiRef::new(vec![0u8; 5]);
+        vec[3] = 9;
+        vec[0] += 1;
+        assert_eq!(vec[3], 9);
+        let (a, b) = (&mut vec.get_mut()[1], &mut vec.get_mut()[1]);
+        *a += 2;
+        *b += 2;
+        assert_eq!(vec.unwrap(), vec![1, 4, 0, 9, 0]);
+
+        let mut array = MultiRef::new([1, 2, 3]);
+        array[2] *= 10;
+        assert_eq!(array[0] + array[2], 31);
+        assert_eq!(array.unwrap(), [1, 2, 30]);
+    }}
+
+    #[test]
+    fn identity() {
+        let a = MultiRef::new(String::from("same"));
+        let b = MultiRef::new(String::from("same"));
+        let c = &a;
+
+        assert!(a == b);
+        assert_ne!(a.addr(), b.addr());
+        assert_ne!(format!("{:p}", a), format!("{:p}", b));
+        assert!(!MultiRef::ptr_eq(&a, &b));
+        assert!(MultiRef::ptr_eq(&a, &a));
+        assert!(MultiRef::ptr_eq(&a, c));
+        assert_eq!(format!("{:p}", a), format!("{:p}", c));
+    }
+
+    #[test]
+    fn reduce() {unsafe {
+        let dst = MultiRef::new(vec![1, 2]);
+        let src = MultiRef::new(vec![3, 4]);
+
+        reduce_into(&dst, &src, |a, b| a.extend_from_slice(b));
+        reduce_into(&dst, &src, |a, b| a.push(b.iter().sum()));
+        assert_eq!(src.unwrap(), vec![3, 4]);
+        assert_eq!(dst.unwrap(), vec![1, 2, 3, 4, 7]);
+    }}
+
+    #[test]
+    fn index_map_and_range() {
+        let map = MultiRef::new(HashMap::from([("a", 1), ("b", 2)]));
+        assert_eq!(map["a"] + map["b"], 3);
+
+        let mut slice = MultiRef::new(vec![1, 2, 3, 4, 5]);
+        assert_eq!(&slice[1..3], &[2, 3]);
+        assert_eq!(&slice[3..], &[4, 5]);
+        slice[..2].copy_from_slice(&[8, 9]);
+        assert_eq!(slice.unwrap(), vec![8, 9, 3, 4, 5]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds() {
+        let multiref = MultiRef::new(vec![1, 2, 3]);
+        let _ = multiref[3];
+    }
+
+    #[test]
+    fn into_iter() {unsafe {
+        let mut multiref = MultiRef::new(vec![1, 2, 3]);
+
+        let i = multiref.get_ref();
+        let mut seen = Vec::new();
+        for x in &multiref {
+            seen.push(*x);
+        }
+        assert_eq!(seen, *i);
+
+        for x in &mut multiref {
+            *x += 10;
+        }
+        assert_eq!(multiref.unwrap(), vec![11, 12, 13]);
+
+        let map = MultiRef::new(HashMap::from([("a", 1)]));
+        assert_eq!((&map).into_iter().next(), Some((&"a", &1)));
+    }}
+
+    #[test]
+    fn try_get_many_mut() {unsafe {
+        let multiref = MultiRef::new([0; 5]);
+
+        let [a, b, c] = multiref.try_get_many_mut([4, 0, 2]).unwrap();
+        *a = 4;
+        *b = 10;
+        *c = 2;
+        *b -= 10;
+        assert_eq!(*multiref.get_ref(), [0, 0, 2, 0, 4]);
+
+        assert_eq!(multiref.try_get_many_mut([1, 3, 1]).err(), Some(IndexError::Duplicate(1)));
+        assert_eq!(multiref.try_get_many_mut([1, 5]).err(), Some(IndexError::OutOfRange(5)));
+        assert_eq!(multiref.try_get_many_mut([]).map(|x : [&mut i32; 0]| x.len()), Ok(0));
+    }}
+
+    #[derive(Debug, PartialEq)]
+    struct Meters(f64);
+
+    impl AddAssign<f64> for Meters {
+        fn add_assign(&mut self, rhs : f64) {
+            self.0 += rhs;
+        }
+    }
+
+    #[test]
+    fn assign_ops() {unsafe {
+        let int = MultiRef::new(0b1100u32);
+        let i = int.get_ref();
+        int.add_assign(4);
+        int.sub_assign(2);
+        int.mul_assign(3);
+        int.div_assign(2);
+        assert_eq!(*i, 21);
+        int.bitor_assign(0b1000);
+        int.bitand_assign(0b11101);
+        int.bitxor_assign(0b1);
+        int.shl_assign(2);
+        int.shr_assign(1u8);
+        assert_eq!(int.unwrap(), 0b111000);
+
+        let float = MultiRef::new(1.5f64);
+        float.mul_assign(4.0);
+        float.sub_assign(0.5);
+        float.div_assign(2.0);
+        assert_eq!(float.unwrap(), 2.75);
+
+        let meters = MultiRef::new(Meters(1.0));
+        meters.add_assign(2.5);
+        assert_eq!(meters.unwrap(), Meters(3.5));
+    }}
+
+    #[test]
+    fn collect_and_extend() {unsafe {
+        let mut multiref : MultiRef<Vec<_>> = (0..5).collect();
+        assert_eq!(*multiref.get_ref(), vec![0, 1, 2, 3, 4]);
+
+        multiref.extend(5..7);
+        let i = multiref.get_ref();
+        multiref.extend_shared(vec![7, 8]);
+        assert_eq!(*i, vec![0, 1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(multiref.unwrap().len(), 9);
+    }}
+
+    #[test]
+    fn diff_since() {unsafe {
+        let multiref = MultiRef::new(vec![String::from("a"), String::from("b"), String::from("c"), String::from("d")]);
+
+        let snapshot = multiref.get_ref().clone();
+        assert_eq!(multiref.diff_since(&snapshot), vec![]);
+
+        let a = multiref.get_mut();
+        let b = multiref.get_mut();
+        a[0] = String::from("x");
+        b[2].push('!');
+        a[3] = String::from("d");
+        assert_eq!(multiref.diff_since(&snapshot), vec![
+            (0, String::from("a"), String::from("x")),
+            (2, String::from("c"), String::from("c!"))
+        ]);
+    }}
+
+    #[test]
+    fn collect_types() {unsafe {
+        let vec : MultiRef<Vec<u32>> = (0..10).collect();
+        assert_eq!(vec.get_ref().iter().sum::<u32>(), 45);
+
+        let string : MultiRef<String> = ['a', 'b', 'c'].into_iter().collect();
+        string.get_mut().push('d');
+        assert_eq!(string.unwrap(), "abcd");
+
+        let map : MultiRef<HashMap<_, _>> = [("a", 1), ("b", 2)].into_iter().collect();
+        *map.get_mut().get_mut("b").unwrap() += 1;
+        assert_eq!(map["a"], 1);
+        assert_eq!(map["b"], 3);
+    }}
+
+    #[test]
+    fn pointer_format() {unsafe {
+        let multiref = MultiRef::new([1u64; 4]);
+
+        let i : *const [u64; 4] = multiref.get_ref();
+        let a : *mut [u64; 4] = multiref.get_mut();
+        assert_eq!(format!("{:p}", multiref), format!("{:p}", i));
+        assert_eq!(format!("{:p}", multiref), format!("{:p}", a));
+        assert_eq!(format!("{:p}", multiref), format!("{:#x}", multiref.addr()));
+    }}
+
+    #[test]
+    fn bind_add() {unsafe {
+        let multiref = MultiRef::new(1.0f32);
+
+        let i = multiref.get_ref();
+        let mut handlers : Vec<Box<dyn FnMut(f32)>> = vec![Box::new(multiref.bind_add()), Box::new(multiref.bind_add())];
+        for (n, handler) in handlers.iter_mut().enumerate() {
+            handler(n as f32 + 0.5);
+            handler(1.0);
+        }
+        assert_eq!(*i, 5.0);
+        drop(handlers);
+        assert_eq!(multiref.unwrap(), 5.0);
+    }}
+
+    fn log_event<C : Extend<String>>(log : &MultiRef<C>, event : &str) {unsafe {
+        log.extend_shared([format!("event: {}", event)]);
+    }}
+
+    fn log_events<C : Extend<String>>(log : &MultiRef<C>, count : usize) {unsafe {
+        log.extend_shared((0..count).map(|n| n.to_string()));
+    }}
+
+    #[test]
+    fn extend_shared_call_sites() {unsafe {
+        let vec = MultiRef::new(Vec::new());
+        let i = vec.get_ref();
+        log_event(&vec, "start");
+        log_events(&vec, 2);
+        log_event(&vec, "stop");
+        assert_eq!(*i, vec!["event: start", "0", "1", "event: stop"]);
+
+        let string = MultiRef::new(String::new());
+        log_event(&string, "a");
+        log_events(&string, 3);
+        string.extend_shared(['!']);
+        assert_eq!(string.unwrap(), "event: a012!");
+
+        let set = MultiRef::new(HashSet::new());
+        log_events(&set, 3);
+        log_events(&set, 4);
+        log_event(&set, "x");
+        assert_eq!(set.len(), 5);
+        assert!(set.contains("3"));
+        assert!(set.contains("event: x"));
+    }}
+
+    #[test]
+    fn replace() {unsafe {
+        let int = MultiRef::new(1);
+        let a = int.get_mut();
+        *a += 1;
+        assert_eq!(int.replace(10), 2);
+        *a += 1;
+        assert_eq!(int.unwrap(), 11);
+
+        let string = MultiRef::new(String::from("old"));
+        let i = string.get_ref();
+        let old = string.replace(String::from("new"));
+        assert_eq!(old, "old");
+        assert_eq!(*i, "new");
+        string.get_mut().push_str(&old);
+        assert_eq!(string.unwrap(), "newold");
+    }}
+
+    #[test]
+    fn compare_and_set_at() {unsafe {
+        let multiref = MultiRef::new([String::from("a"), String::from("b")]);
+
+        let i = multiref.get_ref();
+        assert!(multiref.compare_and_set_at(0, &String::from("a"), String::from("x")));
+        assert_eq!(i[0], "x");
+        assert!(!multiref.compare_and_set_at(1, &String::from("a"), String::from("y")));
+        assert_eq!(i[1], "b");
+        assert!(!multiref.compare_and_set_at(0, &String::from("a"), String::from("z")));
+        assert_eq!(multiref.unwrap(), [String::from("x"), String::from("b")]);
+    }}
+
+    #[test]
+    #[should_panic]
+    fn compare_and_set_at_out_of_bounds() {unsafe {
+        let multiref = MultiRef::new([1, 2, 3]);
+        multiref.compare_and_set_at(3, &1, 2);
+    }}
+
+    #[test]
+    fn iter_mut_aliased() {unsafe {
+        let multiref = MultiRef::new(vec![1, 2, 3, 4]);
+
+        // Every element hands half of its value to the next element.
+        for (i, x) in multiref.iter_mut_aliased().enumerate() {
+            for (j, y) in multiref.iter_mut_aliased().enumerate() {
+                if j == i + 1 {
+                    *y += *x / 2;
+                    *x -= *x / 2;
+                }
+            }
+        }
+        assert_eq!(multiref.unwrap(), vec![1, 1, 2, 6]);
+
+        let array = MultiRef::new([1, 2, 3]);
+        for (i, x) in array.iter_mut_aliased().enumerate() {
+            for (j, y) in array.iter_mut_aliased().enumerate() {
+                if i != j {
+                    *y += *x;
+                }
+            }
+        }
+        assert_eq!(array.unwrap(), [11, 10, 7]);
+    }}
+
+    #[test]
+    fn take_vec() {unsafe {
+        let multiref = MultiRef::new(vec![1, 2, 3]);
+
+        let i = multiref.get_ref();
+        let taken = multiref.take();
+        assert_eq!(taken, vec![1, 2, 3]);
+        assert!(i.is_empty());
+        multiref.get_mut().push(4);
+        assert_eq!(*i, vec![4]);
+    }}
+
+    #[test]
+    fn fill_with_index() {unsafe {
+        let multiref = MultiRef::new([usize::MAX; 5]);
+
+        let i = multiref.get_ref();
+        multiref.fill_with_index(|index| index * index);
+        assert_eq!(*i, [0, 1, 4, 9, 16]);
+        multiref.fill_with_index(|index| i[4 - index]);
+        assert_eq!(multiref.unwrap(), [16, 9, 4, 9, 16]);
+    }}
+
+    struct Dropped<'l> {
+        pub id    : u32,
+        pub drops : &'l Cell<u32>
+    }
+
+    impl Drop for Dropped<'_> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    #[test]
+    fn replace_drops() {unsafe {
+        let drops = Cell::new(0);
+        let multiref = MultiRef::new(Dropped {id : 1, drops : &drops});
+
+        let i = multiref.get_ref();
+        let old = multiref.replace(Dropped {id : 2, drops : &drops});
+        assert_eq!(drops.get(), 0);
+        assert_eq!(old.id, 1);
+        assert_eq!(i.id, 2);
+
+        drop(old);
+        assert_eq!(drops.get(), 1);
+        drop(multiref.replace(Dropped {id : 3, drops : &drops}));
+        assert_eq!(drops.get(), 2);
+        assert_eq!(i.id, 3);
+
+        drop(multiref);
+        assert_eq!(drops.get(), 3);
+    }}
+
+    #[test]
+    fn set() {unsafe {
+        let multiref = MultiRef::new(
+            Test {
+                a : 1,
+                b : false
+            }
+        );
+
+        let i = multiref.get_ref();
+        multiref.set(
+            Test {
+                a : 2,
+                b : true
+            }
+        );
+        assert_eq!((*i).a, 2);
+        assert_eq!((*i).b, true);
+    }}
+
+    #[test]
+    fn as_slice_of_cells() {unsafe {
+        let multiref = MultiRef::new([1u8, 2, 3, 4]);
+
+        let cells = multiref.as_slice_of_cells();
+        assert_eq!(cells.len(), 4);
+        cells[1].set(20);
+        cells[3].set(cells[0].get() + cells[1].get());
+        assert_eq!(cells[3].get(), 21);
+        assert_eq!(cells.iter().map(Cell::get).collect::<Vec<_>>(), vec![1, 20, 3, 21]);
+        assert_eq!(multiref.unwrap(), [1, 20, 3, 21]);
+    }}
+
+    #[test]
+    fn swap() {unsafe {
+        let a = MultiRef::new(1);
+        let b = MultiRef::new(2);
+
+        let i = a.get_ref();
+        a.swap(&b);
+        assert_eq!(*i, 2);
+        assert_eq!(*b.get_ref(), 1);
+
+        a.swap(&a);
+        assert_eq!(*i, 2);
+        assert_eq!(a.unwrap(), 2);
+        assert_eq!(b.unwrap(), 1);
+    }}
+
+    #[test]
+    fn replace_at() {unsafe {
+        let multiref = MultiRef::new(vec![String::from("a"), String::from("b"), String::from("c")]);
+
+        let i = multiref.get_ref();
+        assert_eq!(multiref.replace_at(1, String::from("x")), "b");
+        assert_eq!(*i, vec!["a", "x", "c"]);
+    }}
+
+    #[test]
+    #[should_panic]
+    fn replace_at_out_of_bounds() {unsafe {
+        let multiref = MultiRef::new(vec![1, 2, 3]);
+        multiref.replace_at(3, 4);
+    }}
+
+    #[test]
+    fn map() {unsafe {
+        let number = MultiRef::new(41);
+        *number.get_mut() += 1;
+        let string = number.map(|n| n.to_string());
+        string.get_mut().push('!');
+        assert_eq!(string.unwrap(), "42!");
+
+        let multiref = MultiRef::new(
+            Test {
+                a : 1,
+                b : true
+            }
+        );
+        let field = multiref.map(|test| test.b);
+        assert_eq!(field.unwrap(), true);
+    }}
+
+    struct PanicOnDrop<'l> {
+        pub drops : &'l Cell<u32>
+    }
+
+    impl Drop for PanicOnDrop<'_> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+            panic!("dropped");
+        }
+    }
+
+    #[test]
+    fn set_drops() {unsafe {
+        let drops = Cell::new(0);
+        let multiref = MultiRef::new(Dropped {id : 1, drops : &drops});
+
+        multiref.set(Dropped {id : 2, drops : &drops});
+        assert_eq!(drops.get(), 1);
+        multiref.set_with(|| Dropped {id : multiref.id + 1, drops : &drops});
+        assert_eq!(drops.get(), 2);
+        assert_eq!(multiref.id, 3);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| multiref.set_with(|| panic!("not constructed"))));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 2);
+        assert_eq!(multiref.id, 3);
+
+        drop(multiref);
+        assert_eq!(drops.get(), 3);
+    }}
+
+    #[test]
+    fn set_panic_in_drop() {unsafe {
+        let old_drops = Cell::new(0);
+        let new_drops = Cell::new(0);
+        let multiref = MultiRef::new(Ok(PanicOnDrop {drops : &old_drops}));
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| multiref.set(Err(Dropped {id : 1, drops : &new_drops}))));
+        assert!(result.is_err());
+        assert_eq!(old_drops.get(), 1);
+        assert_eq!(new_drops.get(), 0);
+        assert!(matches!(multiref.get_ref(), Err(Dropped {id : 1, ..})));
+
+        drop(multiref);
+        assert_eq!(old_drops.get(), 1);
+        assert_eq!(new_drops.get(), 1);
+    }}
+
+    struct Buffer<'l> {
+        pub pixels : [u32; 1024],
+        pub rows   : Vec<Vec<u32>>,
+        pub tag    : Dropped<'l>
+    }
+
+    #[test]
+    fn swap_buffers() {unsafe {
+        let drops = Cell::new(0);
+        let front = MultiRef::new(Buffer {pixels : [1; 1024], rows : vec![vec![1; 8]; 8], tag : Dropped {id : 1, drops : &drops}});
+        let back  = MultiRef::new(Buffer {pixels : [2; 1024], rows : vec![vec![2; 8]; 4], tag : Dropped {id : 2, drops : &drops}});
+
+        let renderer = front.get_ref();
+        for _ in 0..3 {
+            front.swap(&back);
+        }
+        assert_eq!(renderer.tag.id, 2);
+        assert_eq!(renderer.pixels[1023], 2);
+        assert_eq!(renderer.rows.len(), 4);
+        assert_eq!(back.tag.id, 1);
+        assert_eq!(back.rows.len(), 8);
+
+        back.swap(&back);
+        assert_eq!(back.tag.id, 1);
+        assert_eq!(back.pixels[0], 1);
+        assert_eq!(drops.get(), 0);
+
+        drop(front);
+        drop(back);
+        assert_eq!(drops.get(), 2);
+    }}
+
+    struct Parent {
+        pub name  : &'static str,
+        pub child : *mut Child
+    }
+
+    struct Child {
+        pub age    : u32,
+        pub parent : *mut Parent
+    }
+
+    #[test]
+    fn pair_cyclic() {unsafe {
+        let (parent, child) = super::pair_cyclic(
+            Parent {name : "parent", child : ptr::null_mut()},
+            Child {age : 0, parent : ptr::null_mut()}
+        );
+        parent.get_mut().child = child.get_mut();
+        child.get_mut().parent = parent.get_mut();
+
+        for _ in 0..3 {
+            (*parent.get_ref().child).age += 1;
+        }
+        (*child.get_ref().parent).name = "renamed";
+        assert_eq!(child.age, 3);
+        assert_eq!(parent.name, "renamed");
+        assert_eq!((*(*child.parent).child).age, 3);
+        assert!(ptr::eq((*parent.child).parent, parent.get_ref()));
+    }}
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Point {
+        pub x : i32,
+        pub y : i32
+    }
+
+    #[test]
+    fn get_and_get_update() {unsafe {
+        let counter = MultiRef::new(0u64);
+        let writer = counter.get_mut();
+        *writer += 5;
+        assert_eq!(counter.get(), 5);
+        assert_eq!(counter.get_update(|n| n + 1), 6);
+        *writer *= 2;
+        assert_eq!(counter.get(), 12);
+
+        let point = MultiRef::new(Point {x : 1, y : 2});
+        let writer = point.get_mut();
+        let before = point.get();
+        writer.x = 10;
+        assert_eq!(before, Point {x : 1, y : 2});
+        assert_eq!(point.get_update(|p| Point {x : p.y, y : p.x}), Point {x : 2, y : 10});
+        assert_eq!(writer.y, 10);
+
+        let vec = MultiRef::new(vec![1, 2]);
+        assert_eq!(vec.get(1), Some(&2));
+    }}
+
+    #[test]
+    fn with_ref_and_with_mut() {unsafe {
+        let multiref = MultiRef::new(
+            Test {
+                a : 1,
+                b : false
+            }
+        );
+
+        let a = multiref.with_ref(|test| test.a);
+        multiref.with_mut(|test| test.a += a);
+        let b = multiref.with_mut(|test| {
+            test.b = !test.b;
+            test.b
+        });
+        assert_eq!(multiref.with_ref(|test| (test.a, test.b)), (2, b));
+    }}
+
+    #[test]
+    fn clone_inner() {unsafe {
+        let counter = MultiRef::new(0);
+        let a = counter.get_mut();
+        let b = counter.get_mut();
+        *a += 1;
+        let first = counter.clone_inner();
+        *b += 2;
+        let second = counter.clone_inner();
+        assert_eq!((first, second), (1, 3));
+
+        let names = MultiRef::new(vec![String::from("a"), String::from("b")]);
+        let mut snapshot = names.clone_inner();
+        names.get_mut()[0].push('!');
+        snapshot[1].push('?');
+        assert_eq!(*names.get_ref(), vec!["a!", "b"]);
+        assert_eq!(snapshot, vec!["a", "b?"]);
+        assert_ne!(names[0].as_ptr(), snapshot[0].as_ptr());
+    }}
+
+    #[test]
+    fn get_many_mut() {unsafe {
+        let multiref = MultiRef::new(vec![0]);
+
+        let refs = multiref.get_many_mut::<3>();
+        for (i, r) in refs.into_iter().enumerate() {
+            r.push(i + 1);
+        }
+        assert_eq!(*multiref.get_ref(), vec![0, 1, 2, 3]);
+
+        let counter = MultiRef::new(10);
+        thread::scope(|scope| {
+            for r in counter.get_many_mut::<4>() {
+                scope.spawn(move || *r += 1).join().unwrap();
+            }
+        });
+        assert_eq!(counter.unwrap(), 14);
+    }}
+
+    #[test]
+    fn split_three_mut() {unsafe {
+        let multiref = MultiRef::new((1..=9).collect::<Vec<_>>());
+
+        let (a, b, c) = multiref.split_three_mut(3, 6);
+        assert_eq!((a.len(), b.len(), c.len()), (3, 3, 3));
+        a.iter_mut().for_each(|x| *x *= 10);
+        b.reverse();
+        c.fill(0);
+        assert_eq!(multiref.unwrap(), vec![10, 20, 30, 6, 5, 4, 0, 0, 0]);
+
+        let edges = MultiRef::new(vec![1, 2]);
+        let (a, b, c) = edges.split_three_mut(0, 2);
+        assert_eq!((a.len(), b.len(), c.len()), (0, 2, 0));
+    }}
+
+    #[test]
+    #[should_panic]
+    fn split_three_mut_out_of_order() {unsafe {
+        let multiref = MultiRef::new(vec![1, 2, 3]);
+        multiref.split_three_mut(2, 1);
+    }}
+
+    #[test]
+    #[should_panic]
+    fn split_three_mut_out_of_bounds() {unsafe {
+        let multiref = MultiRef::new(vec![1, 2, 3]);
+        multiref.split_three_mut(1, 4);
+    }}
+
+    extern "C" fn add_to_all(values : *mut u32, len : usize, amount : u32) {
+        for i in 0..len {
+            unsafe {*values.add(i) += amount};
+        }
+    }
+
+    #[test]
+    fn raw_pointers() {unsafe {
+        let multiref = MultiRef::new([1u32, 2, 3]);
+
+        let i = multiref.get_ref();
+        *(multiref.as_mut_ptr() as *mut u32).add(1) = 20;
+        assert_eq!(*i, [1, 20, 3]);
+
+        add_to_all(multiref.as_mut_ptr() as *mut u32, 3, 5);
+        assert_eq!(*i, [6, 25, 8]);
+        assert_eq!(multiref.as_ptr(), i as *const [u32; 3]);
+        assert_eq!(format!("{:p}", multiref), format!("{:p}", multiref.as_ptr()));
+    }}
+
+    #[test]
+    fn try_unwrap() {unsafe {
+        let multiref = MultiRef::new(String::from("a"));
+
+        multiref.get_mut().push('b');
+        match multiref.try_unwrap() {
+            Ok(value) => assert_eq!(value, "ab"),
+            Err(_)    => panic!("try_unwrap failed")
+        }
+    }}
+
+    #[test]
+    fn as_typed_slice() {unsafe {
+        let multiref = MultiRef::new((0..16).collect::<Vec<u8>>());
+
+        let words = multiref.as_typed_slice::<u32>();
+        assert_eq!(words.len(), 4);
+        assert_eq!(words[1], u32::from_ne_bytes([4, 5, 6, 7]));
+        words[0] = u32::from_ne_bytes([9, 9, 9, 9]);
+        words[3] = 0;
+        assert_eq!(multiref.unwrap(), vec![9, 9, 9, 9, 4, 5, 6, 7, 8, 9, 10, 11, 0, 0, 0, 0]);
+
+        // An empty buffer's dangling pointer is not checked for alignment.
+        let empty = MultiRef::new(Vec::<u8>::new());
+        assert!(empty.as_typed_slice::<u64>().is_empty());
+        assert!(empty.as_typed_slice::<u128>().is_empty());
+    }}
+
+    #[test]
+    #[should_panic]
+    fn as_typed_slice_bad_length() {unsafe {
+        let multiref = MultiRef::new(vec![0u8; 15]);
+        multiref.as_typed_slice::<u32>();
+    }}
+
+    #[test]
+    fn into_raw_and_from_raw() {unsafe {
+        let drops = Cell::new(0);
+        let multiref = MultiRef::new((0, Dropped {id : 1, drops : &drops}));
+
+        multiref.get_mut().0 += 1;
+        let userdata = multiref.into_raw() as usize;
+        assert_eq!(drops.get(), 0);
+
+        let handle = userdata as *mut (i32, Dropped);
+        (*handle).0 += 10;
+        (*handle).1.id = 2;
+
+        let multiref = MultiRef::<(i32, Dropped)>::from_raw(userdata as *mut _);
+        assert_eq!(multiref.get_ref().0, 11);
+        assert_eq!(multiref.get_ref().1.id, 2);
+        assert_eq!(drops.get(), 0);
+        drop(multiref);
+        assert_eq!(drops.get(), 1);
+    }}
+
+    #[test]
+    fn leak() {unsafe {
+        let multiref = MultiRef::new(10u64);
+
+        *multiref.get_mut() += 1;
+        let counter : &'static mut u64 = multiref.leak();
+        for _ in 0..5 {
+            *counter += 1;
+        }
+        assert_eq!(*counter, 16);
+    }}
+
+    #[test]
+    fn map_values() {unsafe {
+        let multiref = MultiRef::new(HashMap::from([
+            (String::from("a"), 1),
+            (String::from("b"), -2),
+            (String::from("c"), 30)
+        ]));
+
+        let i = multiref.get_ref();
+        multiref.map_values(|v| *v *= 2);
+        assert_eq!(i["a"], 2);
+        assert_eq!(i["b"], -4);
+        assert_eq!(i["c"], 60);
+
+        let mut visited = 0;
+        multiref.map_values(|_| visited += 1);
+        assert_eq!(visited, 3);
+    }}
+
+    struct Lens<'l> {
+        pub focal_length : f64,
+        pub tag          : Dropped<'l>
+    }
+
+    #[test]
+    fn map_chain() {unsafe {
+        let drops = Cell::new(0);
+        let camera = MultiRef::new((String::from("camera"), Lens {focal_length : 35.0, tag : Dropped {id : 1, drops : &drops}}));
+
+        camera.get_mut().1.focal_length += 15.0;
+        let lens = camera.map(|(_, lens)| lens);
+        assert_eq!(drops.get(), 0);
+        let tagged = lens.map(|lens| (lens.focal_length, lens.tag));
+        let tag = tagged.try_map(|(focal_length, tag)| if focal_length == 50.0 {Ok(tag)} else {Err(tag)});
+        assert_eq!(drops.get(), 0);
+
+        let tag = tag.ok().unwrap();
+        assert_eq!(tag.id, 1);
+        let failed = tag.try_map(|tag| Err::<(), _>(tag.id));
+        assert_eq!(failed.err(), Some(1));
+        assert_eq!(drops.get(), 1);
+    }}
+
+    #[test]
+    fn into_result() {unsafe {
+        let ok = MultiRef::new(Ok::<i32, String>(1));
+        let err = MultiRef::new(Err::<i32, String>(String::from("failed")));
+        assert!(ok.is_ok() && !ok.is_err());
+        assert!(err.is_err() && !err.is_ok());
+
+        *ok.get_mut() = Ok(2);
+        *err.get_mut() = Err(String::from("failed again"));
+        assert_eq!(ok.into_result(), Ok(2));
+        assert_eq!(err.into_result(), Err(String::from("failed again")));
+    }}
+
+    #[test]
+    fn with_nested_and_panicking() {unsafe {
+        let multiref = MultiRef::new(vec![1, 2, 3]);
+
+        let total = multiref.with_ref(|outer| {
+            multiref.with_mut(|inner| inner.push(outer.len() as i32));
+            multiref.with_ref(|inner| inner.iter().sum::<i32>())
+        });
+        assert_eq!(total, 9);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            multiref.with_mut(|v| {
+                v.push(5);
+                panic!("closure panicked");
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(multiref.with_ref(|v| v.clone()), vec![1, 2, 3, 3, 5]);
+        multiref.with_mut(|v| v.clear());
+        assert!(multiref.is_empty());
+    }}
+
+    #[test]
+    fn replace_with() {unsafe {
+        let multiref = MultiRef::new(String::from("abc"));
+
+        multiref.replace_with(|s| s.to_uppercase());
+        assert_eq!(*multiref.get_ref(), "ABC");
+        multiref.replace_with(|s| {
+            assert_eq!(*multiref.get_ref(), "");
+            s + "!"
+        });
+        assert_eq!(*multiref.get_ref(), "ABC!");
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| multiref.replace_with(|_| panic!("closure panicked"))));
+        assert!(result.is_err());
+        assert_eq!(*multiref.get_ref(), "");
+        multiref.get_mut().push('x');
+        assert_eq!(multiref.unwrap(), "x");
+    }}
+
+    #[test]
+    fn replace_if_some() {unsafe {
+        let slot = MultiRef::new(Some(String::from("old")));
+        let i = slot.get_ref();
+        assert_eq!(slot.replace_if_some(String::from("new")), Some(String::from("old")));
+        assert_eq!(*i, Some(String::from("new")));
+
+        let empty = MultiRef::new(None);
+        let i = empty.get_ref();
+        assert_eq!(empty.replace_if_some(String::from("new")), None);
+        assert_eq!(*i, None);
+    }}
+
+    #[test]
+    fn cast() {unsafe {
+        let multiref = MultiRef::new(0x01020304u32);
+
+        *multiref.get_mut() += 0x10000000;
+        let bytes = multiref.cast::<[u8; 4]>();
+        let mut expected = 0x11020304u32.to_ne_bytes();
+        assert_eq!(*bytes.get_ref(), expected);
+
+        bytes.get_mut()[0] = 0;
+        expected[0] = 0;
+        assert_eq!(bytes.cast::<u32>().unwrap(), u32::from_ne_bytes(expected));
     }}
 
-    struct Test {
-        pub a : i32,
-        pub b : bool
-    }
-    
     #[test]
-    fn struct_mut() {unsafe {
+    #[should_panic]
+    fn cast_size_mismatch() {unsafe {
+        MultiRef::new(1u32).cast::<u64>();
+    }}
+
+    #[test]
+    fn update() {unsafe {
         let multiref = MultiRef::new(
             Test {
                 a : 1,
                 b : false
             }
         );
+        let i = multiref.get_ref();
+        multiref.update(|test| test.a += 1);
+        assert_eq!((*i).a, 2);
+        let old = multiref.with_mut(|test| mem::replace(&mut test.a, 10));
+        assert_eq!(old, 2);
+        assert_eq!((*i).a, 10);
 
-        let x = multiref.get_mut();
-        let y = multiref.get_mut();
-        let z = multiref.get_mut();
-        (*x).a += 10;
-        (*y).b = true;
-        (*z).a += 7;
-    
-        let unwrapped = multiref.unwrap();
-        assert_eq!(unwrapped.a, 18);
-        assert_eq!(unwrapped.b, true);
+        let map = MultiRef::new(HashMap::from([("a", vec![1])]));
+        let i = map.get_ref();
+        map.update(|map| map.get_mut("a").unwrap().push(2));
+        map.update(|map| map.entry("b").or_default().push(3));
+        assert_eq!(i["a"], vec![1, 2]);
+        assert_eq!(i["b"], vec![3]);
     }}
-    
+
+    struct Connection {
+        pub opened : u32,
+        pub sent   : Vec<&'static str>
+    }
+
+    fn send(slot : &MultiRef<Option<Connection>>, opened : &Cell<u32>, message : &'static str) {unsafe {
+        let connection = slot.get_or_insert_with(|| {
+            opened.set(opened.get() + 1);
+            Connection {opened : opened.get(), sent : Vec::new()}
+        });
+        connection.sent.push(message);
+    }}
+
     #[test]
-    fn struct_immut_and_mut() {unsafe {
-        let multiref = MultiRef::new(
-            Test {
-                a : 1,
-                b : false
+    fn option_slot() {unsafe {
+        let opened = Cell::new(0);
+        let slot = MultiRef::new(None);
+        assert!(slot.is_none());
+        assert!(slot.as_inner_ref().is_none());
+        assert!(slot.as_inner_mut().is_none());
+
+        // Two modules racing to initialise the empty slot share the first connection.
+        send(&slot, &opened, "a");
+        send(&slot, &opened, "b");
+        assert!(slot.is_some());
+        assert_eq!(opened.get(), 1);
+        assert_eq!(slot.as_inner_ref().unwrap().sent, vec!["a", "b"]);
+
+        let reader = slot.as_inner_ref().unwrap();
+        assert_eq!(reader.opened, 1);
+        let taken = slot.take_inner().unwrap();
+        assert!(!slot.is_some());
+        assert!(slot.as_inner_ref().is_none());
+        assert!(slot.take_inner().is_none());
+        assert_eq!(taken.sent, vec!["a", "b"]);
+
+        send(&slot, &opened, "c");
+        assert_eq!(opened.get(), 2);
+        slot.as_inner_mut().unwrap().sent.push("d");
+        assert_eq!(slot.as_inner_ref().unwrap().sent, vec!["c", "d"]);
+
+        let replaced = slot.insert(Connection {opened : 0, sent : vec!["e"]});
+        replaced.sent.push("f");
+        assert_eq!(slot.unwrap().unwrap().sent, vec!["e", "f"]);
+    }}
+
+
+    #[test]
+    fn new_default() {unsafe {
+        let multiref = MultiRef::<Vec<i32>>::new_default();
+        assert!(multiref.is_empty());
+
+        let reader = multiref.get_ref();
+        multiref.extend_shared([1, 2]);
+        multiref.get_mut().push(3);
+        assert_eq!(*reader, vec![1, 2, 3]);
+
+        let other : MultiRef<Vec<i32>> = MultiRef::default();
+        assert_eq!(other.unwrap(), Vec::<i32>::new());
+    }}
+
+
+    #[test]
+    fn field_ptr() {unsafe {
+        let point = MultiRef::new(Point {x : 1, y : 2});
+        let reader = point.get_ref();
+
+        let x = multiref_field_ptr!(&point, Point, x);
+        let y = multiref_field_ptr!(&point, Point, y);
+        *x = 5;
+        *y += 10;
+        assert_eq!(reader.x, 5);
+        assert_eq!(reader.y, 12);
+        assert_eq!(x as usize, &reader.x as *const i32 as usize);
+        assert_eq!(y as usize, &reader.y as *const i32 as usize);
+    }}
+
+
+    struct Stats {
+        pub health : u32,
+        pub armour : u32
+    }
+
+    struct Player {
+        pub name  : String,
+        pub stats : Stats
+    }
+
+    fn damage(health : &MultiRef<u32>, amount : u32) {unsafe {
+        *health.get_mut() -= amount;
+    }}
+
+    #[test]
+    fn map_ref() {unsafe {
+        let player = MultiRef::new(Player {
+            name  : String::from("a"),
+            stats : Stats {health : 100, armour : 5}
+        });
+        let reader = player.get_ref();
+
+        let name = player.map_ref(|player| &mut player.name);
+        name.get_mut().push('b');
+        assert_eq!(reader.name, "ab");
+
+        let stats = player.map_ref(|player| &mut player.stats);
+        let health = stats.map_ref(|stats| &mut stats.health);
+        let armour = player.map_ref(|player| &mut player.stats.armour);
+        damage(health, 30);
+        *armour.get_mut() += 1;
+        assert_eq!(*health.get_ref(), 70);
+        assert_eq!(stats.get_ref().armour, 6);
+        assert_eq!(reader.stats.health, 70);
+
+        player.get_mut().stats.health = 1;
+        assert_eq!(**health, 1);
+        assert!(ptr::eq(health.as_ptr(), &reader.stats.health));
+    }}
+
+
+    #[test]
+    fn ptr_eq() {
+        let a = MultiRef::new(vec![1]);
+        let b = MultiRef::new(vec![1]);
+        let views = [&a, &b, &a];
+
+        assert!(MultiRef::ptr_eq(&a, &a));
+        assert!(!MultiRef::ptr_eq(&a, &b));
+        assert!(MultiRef::ptr_eq(views[0], views[2]));
+        assert!(!MultiRef::ptr_eq(views[1], views[2]));
+
+        let moved = a;
+        assert!(MultiRef::ptr_eq(&moved, &moved));
+        assert!(!MultiRef::ptr_eq(&moved, &b));
+    }
+
+
+    struct SyncWrapper<T>(T);
+    unsafe impl<T> Sync for SyncWrapper<T> {}
+
+    static SCORE : SyncWrapper<MultiRef<u64>> = SyncWrapper(MultiRef::new(0));
+
+    const fn score_ptr(cell : &MultiRef<u64>) -> *const u64 {
+        return cell.as_ptr();
+    }
+
+    fn add_score(points : u64) {unsafe {
+        *SCORE.0.get_mut() += points;
+    }}
+
+    #[test]
+    fn static_cell() {unsafe {
+        let reader = SCORE.0.get_ref();
+        add_score(5);
+        add_score(7);
+        assert_eq!(*reader, 12);
+        assert_eq!(*score_ptr(&SCORE.0), 12);
+        assert!(ptr::eq(SCORE.0.as_ptr(), reader));
+    }}
+
+
+    #[test]
+    fn merge() {
+        let a = MultiRef::new(vec![1, 3, 3, 8, 10]);
+        let b = MultiRef::new(vec![0, 3, 4, 11, 12]);
+        let empty = MultiRef::new(Vec::new());
+
+        assert_eq!(merge_sorted(&a, &b), vec![0, 1, 3, 3, 3, 4, 8, 10, 11, 12]);
+        assert_eq!(merge_sorted(&b, &a), merge_sorted(&a, &b));
+        assert_eq!(merge_sorted(&a, &empty), *a);
+        assert_eq!(merge_sorted(&empty, &b), *b);
+        assert_eq!(merge_sorted(&a, &a), vec![1, 1, 3, 3, 3, 3, 8, 8, 10, 10]);
+        assert_eq!(a.unwrap(), vec![1, 3, 3, 8, 10]);
+        assert_eq!(b.unwrap(), vec![0, 3, 4, 11, 12]);
+    }
+
+
+    struct CycleNode<'l> {
+        pub value : Dropped<'l>,
+        pub other : *const CycleNode<'l>
+    }
+
+    #[test]
+    fn uninit_cycle() {unsafe {
+        let drops = Cell::new(0);
+
+        let a = MultiRef::<CycleNode>::new_uninit();
+        let b = MultiRef::<CycleNode>::new_uninit();
+        a.write(CycleNode {value : Dropped {id : 1, drops : &drops}, other : b.as_ptr().cast()});
+        b.write(CycleNode {value : Dropped {id : 2, drops : &drops}, other : a.as_ptr().cast()});
+
+        let a_ref = a.assume_init_ref();
+        assert_eq!((*a_ref.other).value.id, 2);
+        assert_eq!((*(*a_ref.other).other).value.id, 1);
+        b.assume_init_mut().value.id = 3;
+        assert_eq!((*a_ref.other).value.id, 3);
+
+        let (a, b) = (a.assume_init(), b.assume_init());
+        assert_eq!(drops.get(), 0);
+        drop(a);
+        drop(b);
+        assert_eq!(drops.get(), 2);
+
+        // An uninitialised cell does not drop garbage, and a written one leaks without `assume_init()`.
+        {
+            let _empty = MultiRef::<Dropped>::new_uninit();
+            let leaked = MultiRef::<Dropped>::new_uninit();
+            leaked.write(Dropped {id : 4, drops : &drops});
+        }
+        assert_eq!(drops.get(), 2);
+    }}
+
+
+    #[test]
+    fn with_mut_retry() {unsafe {
+        let multiref = MultiRef::new(10);
+        let reader = multiref.get_ref();
+        let mut attempts = 0;
+
+        let result = multiref.with_mut_retry(|value| {
+            attempts += 1;
+            *value += 1;
+            if attempts == 3 {
+                return Some(*value * 2);
             }
-        );
+            return None;
+        });
+        assert_eq!(result, 26);
+        assert_eq!(attempts, 3);
+        assert_eq!(*reader, 13);
+    }}
 
-        let i = multiref.get_ref();
-        assert_eq!((*i).a, 1);
-        assert_eq!((*i).b, false);
 
-        let x = multiref.get_mut();
-        let y = multiref.get_mut();
-        let z = multiref.get_mut();
-        (*x).a += 10;
-        (*y).b = true;
-        (*z).a += 7;
-        assert_eq!((*i).a, 18);
-        assert_eq!((*i).b, true);
+    struct Frame {
+        pub id      : u64,
+        pub width   : u16,
+        pub height  : u16,
+        pub gain    : f32,
+        pub samples : [i16; 1024]
+    }
 
-        let unwrapped = multiref.unwrap();
-        assert_eq!(unwrapped.a, 18);
-        assert_eq!(unwrapped.b, true);
+    unsafe impl Zeroable for Frame {}
+
+    #[test]
+    fn new_zeroed() {unsafe {
+        let buffer = MultiRef::<[u8; 65536]>::new_zeroed();
+        assert!(buffer.get_ref().iter().all(|byte| *byte == 0));
+        buffer.get_mut()[65535] = 1;
+        assert_eq!(buffer.get_ref().iter().map(|byte| *byte as u32).sum::<u32>(), 1);
+
+        let frame = MultiRef::<Frame>::new_zeroed();
+        assert_eq!((frame.id, frame.width, frame.height, frame.gain), (0, 0, 0, 0.0));
+        assert_eq!(frame.samples, [0; 1024]);
+        frame.get_mut().samples[3] = -4;
+        assert_eq!(frame.unwrap().samples[..4], [0, 0, 0, -4]);
+
+        let frame = MultiRef::<Frame>::zeroed();
+        assert_eq!(frame.samples, [0; 1024]);
+        let pair = MultiRef::<(u8, *const Frame)>::zeroed();
+        assert!(pair.1.is_null());
     }}
 
-    // THIS IS, FOR THE MOST PART, A TERRIBLE IDEA. IF YOU DO THIS, MAKE SURE YOU KNOW WHAT YOU'RE DOING.
+
+    struct Config {
+        pub name    : String,
+        pub retries : u32
+    }
+
+    fn setup_config() -> (&'static mut Config, &'static Vec<u32>) {
+        let config = MultiRef::new(Config {name : String::from("app"), retries : 1});
+        let limits = MultiRef::new(vec![10, 20]);
+        return (config.leak(), limits.leak_ref());
+    }
+
+    fn bump_retries(config : &'static mut Config) -> &'static mut Config {
+        config.retries += 1;
+        return config;
+    }
+
     #[test]
-    fn threads() {unsafe {
-        let a = 10;
-        let b = 10;
-        let c = 100;
-        let d = 1;
+    fn leak_static() {
+        let (config, limits) = setup_config();
+        let config = bump_retries(config);
+        assert_eq!(config.retries, 2);
 
-        let multiref = MultiRef::new(a);
+        let config : &'static Config = config;
+        let total = thread::spawn(move || {
+            return limits.iter().sum::<u32>() + config.retries;
+        }).join().unwrap();
+        assert_eq!(total, 32);
+        assert_eq!(config.name, "app");
+    }
 
-        for _ in 0..b {
-            thread::scope(|scope| {
-                let mutref = multiref.get_mut();
-                scope.spawn(|| {
-                    for _ in 0..c {
-                        *mutref += d;
-                    }
-                });
-            });
+
+    fn shout(text : Cow<str>) -> String {
+        return text.to_uppercase();
+    }
+
+    #[test]
+    fn as_cow_str() {unsafe {
+        let multiref = MultiRef::new(String::from("hello"));
+
+        let cow = multiref.as_cow_str();
+        assert!(matches!(cow, Cow::Borrowed("hello")));
+        assert_eq!(shout(multiref.as_cow_str()), "HELLO");
+
+        let owned = cow.into_owned();
+        multiref.get_mut().push_str(" world");
+        assert_eq!(owned, "hello");
+        assert_eq!(multiref.as_cow_str(), "hello world");
+    }}
+
+
+    #[test]
+    fn sync_if_different() {unsafe {
+        let drops = Cell::new(0);
+        let a = MultiRef::new(vec![1, 2]);
+        let b = MultiRef::new(vec![3]);
+
+        assert!(super::sync_if_different(&a, &b));
+        assert_eq!(*b.get_ref(), vec![1, 2]);
+        a.get_mut().push(3);
+        assert!(super::sync_if_different(&a, &b));
+        assert_eq!(*b.get_ref(), vec![1, 2, 3]);
+        assert!(!super::sync_if_different(&a, &a));
+
+        // Equal values leave the destination untouched, so nothing is cloned or dropped.
+        #[derive(Clone)]
+        struct Tracked<'l>(u32, &'l Cell<u32>);
+        impl PartialEq for Tracked<'_> {
+            fn eq(&self, other : &Self) -> bool {
+                return self.0 == other.0;
+            }
         }
+        impl Drop for Tracked<'_> {
+            fn drop(&mut self) {
+                self.1.set(self.1.get() + 1);
+            }
+        }
+        let src = MultiRef::new(Tracked(1, &drops));
+        let dst = MultiRef::new(Tracked(1, &drops));
+        assert!(!super::sync_if_different(&src, &dst));
+        assert_eq!(drops.get(), 0);
+        src.get_mut().0 = 2;
+        assert!(super::sync_if_different(&src, &dst));
+        assert_eq!(drops.get(), 1);
+        assert_eq!(dst.get_ref().0, 2);
+    }}
 
-        assert_eq!(multiref.unwrap(), a + b * c * d);
+
+    // Counts down once per poll, and checks that it has not moved since it was first polled.
+    struct Countdown {
+        pub remaining : u32,
+        pub address   : *const Countdown,
+        _pinned       : PhantomPinned
+    }
+
+    impl Future for Countdown {
+        type Output = &'static str;
+        fn poll(self : Pin<&mut Self>, context : &mut Context) -> Poll<&'static str> {
+            let this = unsafe {self.get_unchecked_mut()};
+            if this.address.is_null() {
+                this.address = this;
+            }
+            assert!(ptr::eq(this.address, this));
+            if this.remaining == 0 {
+                return Poll::Ready("done");
+            }
+            this.remaining -= 1;
+            context.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+    }
+
+    #[test]
+    fn pinned_future() {unsafe {
+        let countdown = Countdown {remaining : 3, address : ptr::null(), _pinned : PhantomPinned};
+        let pinned = MultiRef::new(countdown).pinned();
+        let reader = pinned.get_ref();
+        let mut context = Context::from_waker(Waker::noop());
+
+        let mut polls = 0;
+        while pinned.get_pin_mut().poll(&mut context).is_pending() {
+            polls += 1;
+            assert_eq!(reader.remaining, 3 - polls);
+        }
+        assert_eq!(polls, 3);
+        assert!(ptr::eq(reader.address, pinned.as_ptr()));
+
+        let moved = pinned;
+        assert_eq!(moved.get_pin_mut().poll(&mut context), Poll::Ready("done"));
     }}
 
-}
+
+    #[test]
+    fn apply_windows() {unsafe {
+        let samples = MultiRef::new(vec![1.0, 2.0, 6.0, 4.0, 5.0]);
+        let reader = samples.get_ref();
+
+        let average = samples.apply_windows(3, |w| w.iter().sum::<f64>() / w.len() as f64);
+        assert_eq!(average.len(), samples.len() - 2);
+        assert_eq!(average, vec![3.0, 4.0, 5.0]);
+        assert_eq!(*reader, vec![1.0, 2.0, 6.0, 4.0, 5.0]);
+
+        assert_eq!(samples.apply_windows(5, |w| w.len()), vec![5]);
+        assert!(samples.apply_windows(6, |w| w.len()).is_empty());
+    }}
+
+
+    #[derive(Debug, PartialEq)]
+    enum Phase {
+        Loading(String),
+        Ready(u32),
+        Failed
+    }
+
+    #[test]
+    fn compare_and_set() {unsafe {
+        let phase = MultiRef::new(Phase::Loading(String::from("assets")));
+        let reader = phase.get_ref();
+
+        let previous = phase.compare_and_set(&Phase::Loading(String::from("assets")), Phase::Ready(1));
+        assert_eq!(previous, Ok(Phase::Loading(String::from("assets"))));
+        assert_eq!(*reader, Phase::Ready(1));
+
+        let rejected = phase.compare_and_set(&Phase::Loading(String::from("assets")), Phase::Failed);
+        assert_eq!(rejected, Err(Phase::Failed));
+        assert_eq!(*reader, Phase::Ready(1));
+
+        assert!(phase.compare_and_update(&Phase::Ready(1), |phase| {
+            if let Phase::Ready(count) = phase {
+                *count += 1;
+            }
+        }));
+        assert!(!phase.compare_and_update(&Phase::Ready(1), |phase| *phase = Phase::Failed));
+        assert_eq!(phase.unwrap(), Phase::Ready(2));
+    }}
+
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn assert_distinct_from() {
+        let a = MultiRef::new([0u8; 4]);
+        let b = MultiRef::new([0u8; 4]);
+        let c = MultiRef::new([0u8; 4]);
+
+        a.assert_distinct_from(&[]);
+        a.assert_distinct_from(&[b.as_ptr(), c.as_ptr()]);
+        b.assert_distinct_from(&[a.as_ptr(), c.as_ptr()]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "is aliased by pointer 1 of 2")]
+    fn assert_distinct_from_self() {
+        let a = MultiRef::new(String::new());
+        let b = MultiRef::new(String::new());
+        let view = &a;
+
+        a.assert_distinct_from(&[b.as_ptr(), view.as_ptr()]);
+    }
+
+
+    fn block_on<F : Future>(future : F) -> F::Output {
+        let mut future = Box::pin(future);
+        let mut context = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    struct Ready(u32);
+
+    impl IntoFuture for Ready {
+        type Output     = u32;
+        type IntoFuture = std::future::Ready<u32>;
+        fn into_future(self) -> std::future::Ready<u32> {
+            return std::future::ready(self.0);
+        }
+    }
+
+    #[test]
+    fn into_future() {unsafe {
+        let multiref = MultiRef::new(Ready(1));
+        let a = multiref.get_mut();
+        let b = multiref.get_mut();
+        a.0 += 10;
+        b.0 += 100;
+
+        assert_eq!(block_on(async {multiref.await}), 111);
+
+        let countdown = MultiRef::new(Countdown {remaining : 2, address : ptr::null(), _pinned : PhantomPinned});
+        assert_eq!(block_on(countdown.into_future()), "done");
+    }}
+
+
+    fn add_health(health : &MultiRef<i32>, amount : i32) {unsafe {
+        *health.get_mut() += amount;
+    }}
+
+    fn double_health(health : &MultiRef<i32>) {unsafe {
+        *health.get_mut() *= 2;
+    }}
+
+    #[test]
+    fn from_mut() {unsafe {
+        let mut health = 10;
+
+        let multiref = MultiRef::from_mut(&mut health);
+        let reader = multiref.get_ref();
+        add_health(multiref, 5);
+        double_health(multiref);
+        add_health(multiref, -1);
+        assert_eq!(*reader, 29);
+        assert_eq!(health, 29);
+
+        let mut values = vec![1, 2, 3];
+        let multiref = MultiRef::from_mut(&mut values);
+        for x in multiref.iter_mut_aliased() {
+            *x += multiref[0];
+        }
+        assert_eq!(values, vec![2, 4, 5]);
+    }}
+
+
+    #[test]
+    fn swap_with_ptr() {unsafe {
+        let multiref = MultiRef::new(String::from("cell"));
+        let reader = multiref.get_ref();
+        let mut local = String::from("stack");
+
+        multiref.swap_with_ptr(&mut local);
+        assert_eq!(*reader, "stack");
+        assert_eq!(local, "cell");
+
+        multiref.swap_with_ptr(multiref.as_mut_ptr());
+        assert_eq!(*reader, "stack");
+
+        let mut values = [1, 2];
+        let numbers = MultiRef::new(0);
+        numbers.swap_with_ptr(values.as_mut_ptr().add(1));
+        assert_eq!(values, [1, 0]);
+        assert_eq!(numbers.get(), 2);
+    }}
+
+    #[test]
+    fn as_slice_of_refs() {unsafe {
+        let multiref = MultiRef::new([0, 1, 2, 3, 4]);
+        let parent : &MultiRef<[i32]> = &multiref;
+        let reader = parent.get_ref();
+
+        // Every element cell aliases the matching element of the parent.
+        let cells = parent.as_slice_of_refs();
+        assert_eq!(cells.len(), 5);
+        *cells[3].get_mut() = 30;
+        assert_eq!(reader[3], 30);
+        assert_eq!(parent.get_ref()[3], 30);
+        assert_eq!(cells[3].as_ptr(), &parent.get_ref()[3] as *const i32);
+        assert_eq!(multiref.as_slice_of_refs()[3].get(), 30);
+
+        let vec = MultiRef::new(vec![String::from("a"), String::from("b")]);
+        let cells = vec.as_slice_of_refs();
+        cells[0].get_mut().push('!');
+        cells[1].swap(&cells[0]);
+        assert_eq!(vec.unwrap(), vec![String::from("b"), String::from("a!")]);
+
+        // Empty slices split into no cells.
+        let empty = MultiRef::new([0u8; 0]);
+        assert!(empty.as_slice_of_refs().is_empty());
+        let empty = MultiRef::new(Vec::<u8>::new());
+        assert!(empty.as_slice_of_refs().is_empty());
+        let mut storage : [i32; 0] = [];
+        assert!(MultiRef::from_mut(&mut storage[..]).as_slice_of_refs().is_empty());
+    }}
+
+    #[test]
+    fn parallel_sum() {
+        let multiref = MultiRef::new(std::array::from_fn::<u64, 1000, _>(|i| (i as u64) * 3 + 1));
+        let sequential = unsafe {multiref.get_ref()}.iter().sum::<u64>();
+        assert_eq!(multiref.parallel_sum(), sequential);
+        assert_eq!(sequential, 1_499_500);
+
+        let floats = MultiRef::new([0.5f64; 1000]);
+        assert_eq!(floats.parallel_sum(), 500.0);
+        assert_eq!(MultiRef::new([1]).parallel_sum(), 1);
+        assert_eq!(MultiRef::new([0i32; 0]).parallel_sum(), 0);
+    }
+
+    #[test]
+    fn get_disjoint_mut() {unsafe {
+        // Two and three distinct indices, on each kind of container.
+        let vec = MultiRef::new(vec![1, 2, 3, 4]);
+        let [a, b] = vec.get_disjoint_mut([0, 3]).unwrap();
+        *a += 10;
+        *b += 10;
+        let [x, y, z] = vec.get_disjoint_mut([3, 1, 2]).unwrap();
+        *x += *y + *z;
+        assert_eq!(*vec.get_ref(), vec![11, 2, 3, 19]);
+
+        let array = MultiRef::new([1, 2, 3]);
+        let slice : &MultiRef<[i32]> = &array;
+        let [a, b] = array.get_disjoint_mut([2, 0]).unwrap();
+        let [c, d, e] = slice.get_disjoint_mut([0, 1, 2]).unwrap();
+        std::mem::swap(a, b);
+        *d = *c + *e;
+        assert_eq!(*array.get_ref(), [3, 4, 1]);
+
+        // Duplicate and out-of-range indices are rejected.
+        assert!(vec.get_disjoint_mut([1, 1]).is_none());
+        assert!(vec.get_disjoint_mut([0, 2, 0]).is_none());
+        assert!(vec.get_disjoint_mut([4]).is_none());
+        assert!(array.get_disjoint_mut([0, 3]).is_none());
+        assert!(slice.get_disjoint_mut([2, 2]).is_none());
+        assert_eq!(slice.try_get_many_mut([5, 5]).err(), Some(IndexError::OutOfRange(5)));
+        assert!(MultiRef::new(Vec::<i32>::new()).get_disjoint_mut([0]).is_none());
+        assert_eq!(vec.get_disjoint_mut([]), Some([]));
+
+        assert_eq!(vec.unwrap(), vec![11, 2, 3, 19]);
+        assert_eq!(array.unwrap(), [3, 4, 1]);
+    }}
+
+}
\ No newline at end of file
@@ -0,0 +1,242 @@
+use std::cell::UnsafeCell;
+
+
+/// A slice whose elements can each have multiple immutable or mutable references at once.
+/// Unlike a `MultiRef<Vec<T>>`, each element is reached directly, without a reference to the whole container.
+/// 
+/// # Broken Borrow Checker Rules
+/// 
+/// * [ ] Variables must be initialised before use.
+/// * [ ] Values can not be moved more than once.
+/// * [ ] Values can not be moved while borrowed.
+/// * [x] Values can not be accessed while mutably borrowed.
+/// * [x] Values can not be mutated while immutably borrowed.
+/// 
+/// # Generics
+/// 
+/// * `T` : The type of the elements.
+/// 
+/// # Warning
+/// 
+/// * This structure is not thread safe.
+/// * You are responsible for preventing undefined behaviour.
+/// * IN MOST CASES THIS SHOULD NOT BE USED DUE TO THE UNPREDICTABLE AND DANGEROUS NATURE OF THIS SYSTEM.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiSlice;
+/// let multislice = MultiSlice::new(vec![1, 2, 3]);
+/// 
+/// let first = unsafe {multislice.get_mut(0)};
+/// let last  = unsafe {multislice.get_mut(2)};
+/// std::mem::swap(first, last);
+/// assert_eq!(multislice.into_vec(), vec![3, 2, 1]);
+/// ```
+/// 
+pub struct MultiSlice<T>(Box<[UnsafeCell<T>]>);
+
+impl<T> MultiSlice<T> {
+
+    /// Create a new `MultiSlice` instance from the elements of a `Vec`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `elements` : The elements to wrap. Spare capacity is released.
+    /// 
+    /// # Returns
+    /// 
+    /// The created `MultiSlice` instance.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiSlice;
+    /// let multislice = MultiSlice::new(vec![1, 2, 3]);
+    /// ```
+    /// 
+    pub fn new(elements : Vec<T>) -> MultiSlice<T> {
+        let elements = Box::into_raw(elements.into_boxed_slice());
+        // `UnsafeCell<T>` has the same layout as `T`, so the allocation carries over.
+        return MultiSlice(unsafe {Box::from_raw(elements as *mut [UnsafeCell<T>])});
+    }
+
+    /// Get the number of elements.
+    /// 
+    /// # Returns
+    /// 
+    /// The number of elements in the slice.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiSlice;
+    /// let multislice = MultiSlice::new(vec![1, 2, 3]);
+    /// 
+    /// assert_eq!(multislice.len(), 3);
+    /// ```
+    /// 
+    pub fn len(&self) -> usize {
+        return self.0.len();
+    }
+
+    /// Check whether there are no elements.
+    /// 
+    /// # Returns
+    /// 
+    /// `true` if the slice is empty.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiSlice;
+    /// let multislice = MultiSlice::<i32>::new(Vec::new());
+    /// 
+    /// assert!(multislice.is_empty());
+    /// ```
+    /// 
+    pub fn is_empty(&self) -> bool {
+        return self.0.is_empty();
+    }
+
+    /// Get an immutable reference to an element.
+    /// Can be used simultaneously with `get_mut()`s or other `get_ref()`s, to the same or other elements.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `index` : The index of the element.
+    /// 
+    /// # Returns
+    /// 
+    /// An immutable reference to the element.
+    /// 
+    /// # Panics
+    /// 
+    /// If `index` is out of range.
+    /// 
+    /// # Safety
+    /// 
+    /// The caller must make sure that the element is not being written to through another reference
+    /// while the returned reference is in use.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiSlice;
+    /// let multislice = MultiSlice::new(vec![1, 2, 3]);
+    /// 
+    /// assert_eq!(unsafe {*multislice.get_ref(1)}, 2);
+    /// ```
+    /// 
+    pub unsafe fn get_ref(&self, index : usize) -> &T {
+        return &*self.0[index].get();
+    }
+
+    /// Get a mutable reference to an element.
+    /// Can be used simultaneously with `get_ref()`s or other `get_mut()`s, to the same or other elements.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `index` : The index of the element.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable reference to the element.
+    /// 
+    /// # Panics
+    /// 
+    /// If `index` is out of range.
+    /// 
+    /// # Safety
+    /// 
+    /// The returned reference aliases every other reference to the same element obtained from this `MultiSlice`.
+    /// References to other elements are not affected.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiSlice;
+    /// let multislice = MultiSlice::new(vec![1, 2, 3]);
+    /// 
+    /// let a = unsafe {multislice.get_mut(1)};
+    /// let b = unsafe {multislice.get_mut(1)};
+    /// *a += 10;
+    /// *b += 10;
+    /// assert_eq!(multislice.into_vec(), vec![1, 22, 3]);
+    /// ```
+    /// 
+    pub unsafe fn get_mut(&self, index : usize) -> &mut T {
+        return &mut *self.0[index].get();
+    }
+
+    /// Return the elements and drop the `MultiSlice`.
+    /// 
+    /// # Returns
+    /// 
+    /// A `Vec` holding the elements, reusing the same allocation.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiSlice;
+    /// let multislice = MultiSlice::new(vec![1, 2, 3]);
+    /// 
+    /// assert_eq!(multislice.into_vec(), vec![1, 2, 3]);
+    /// ```
+    /// 
+    pub fn into_vec(self) -> Vec<T> {
+        let elements = Box::into_raw(self.0);
+        return unsafe {Box::from_raw(elements as *mut [T])}.into_vec();
+    }
+
+}
+
+/// Collect the elements of an iterator into a `MultiSlice`.
+impl<T> FromIterator<T> for MultiSlice<T> {
+    fn from_iter<I : IntoIterator<Item = T>>(iter : I) -> MultiSlice<T> {
+        return MultiSlice::new(iter.into_iter().collect());
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Adds every element into the one after it, keeping a reference to both ends of each step alive.
+    fn prefix_sum(values : &MultiSlice<i32>) {unsafe {
+        for i in 1..values.len() {
+            let previous = values.get_ref(i - 1);
+            let current  = values.get_mut(i);
+            *current += *previous;
+        }
+    }}
+
+    #[test]
+    fn elements() {unsafe {
+        let multislice = (1..=5).collect::<MultiSlice<i32>>();
+        assert_eq!(multislice.len(), 5);
+        assert!(!multislice.is_empty());
+
+        let a = multislice.get_mut(0);
+        let b = multislice.get_mut(4);
+        *a *= 100;
+        *b *= 10;
+        assert_eq!(*multislice.get_ref(4), 50);
+        assert_eq!(*multislice.get_ref(0), 100);
+
+        prefix_sum(&multislice);
+        assert_eq!(multislice.into_vec(), vec![100, 102, 105, 109, 159]);
+    }}
+
+    #[test]
+    #[should_panic]
+    fn out_of_range() {unsafe {
+        let multislice = MultiSlice::new(vec![1]);
+        multislice.get_mut(1);
+    }}
+
+}
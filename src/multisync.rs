@@ -0,0 +1,197 @@
+use std::cell::UnsafeCell;
+use std::sync::atomic::{self, Ordering};
+
+
+/// A `MultiRef` that can be sent and shared across threads.
+/// 
+/// # Broken Borrow Checker Rules
+/// 
+/// * [ ] Variables must be initialised before use.
+/// * [ ] Values can not be moved more than once.
+/// * [ ] Values can not be moved while borrowed.
+/// * [x] Values can not be accessed while mutably borrowed.
+/// * [x] Values can not be mutated while immutably borrowed.
+/// 
+/// # Generics
+/// 
+/// * `T` : The type of the wrapped value.
+/// 
+/// # Thread Safety
+/// 
+/// `MultiSync<T>` is `Send` and `Sync` when `T` is `Send`, so a `&MultiSync<T>` can be handed to other threads.
+/// `get_ref()` issues an acquire fence and `get_mut()` an acquire-release fence, so accesses are not reordered
+/// across them. Fences only synchronise with other atomic operations, so they do not stop two threads from touching
+/// the value at the same time.
+/// 
+/// # Warning
+/// 
+/// * Data races are still your responsibility. Two threads accessing the value at once, with at least one writing,
+///   is undefined behaviour. Use a `Mutex` or join the threads in between.
+/// * IN MOST CASES THIS SHOULD NOT BE USED DUE TO THE UNPREDICTABLE AND DANGEROUS NATURE OF THIS SYSTEM.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiSync;
+/// use std::thread;
+/// let multisync = MultiSync::new(10);
+/// 
+/// thread::scope(|scope| {
+///     scope.spawn(|| unsafe {*multisync.get_mut() += 1});
+/// });
+/// assert_eq!(multisync.unwrap(), 11);
+/// ```
+/// 
+pub struct MultiSync<T>(UnsafeCell<T>);
+
+unsafe impl<T : Send> Send for MultiSync<T> {}
+unsafe impl<T : Send> Sync for MultiSync<T> {}
+
+impl<T> MultiSync<T> {
+
+    /// Create a new `MultiSync` instance.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `object` : The object to wrap in the created `MultiSync`.
+    /// 
+    /// # Returns
+    /// 
+    /// The created `MultiSync` instance.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiSync;
+    /// let multisync = MultiSync::new(10);
+    /// ```
+    /// 
+    pub const fn new(object : T) -> MultiSync<T> {
+        return MultiSync(UnsafeCell::new(object));
+    }
+
+    /// Get an immutable reference to the wrapped value, after an acquire fence.
+    /// Can be used simultaneously with `get_mut()`s or other `get_ref()`s.
+    /// 
+    /// # Returns
+    /// 
+    /// An immutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The caller must make sure that the wrapped value is not being written to through another reference,
+    /// on this thread or any other, while the returned reference is in use.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiSync;
+    /// let multisync = MultiSync::new(10);
+    /// 
+    /// let i = unsafe {multisync.get_ref()};
+    /// assert_eq!(*i, 10);
+    /// ```
+    /// 
+    pub unsafe fn get_ref(&self) -> &T {
+        atomic::fence(Ordering::Acquire);
+        return &*self.0.get();
+    }
+
+    /// Get a mutable reference to the wrapped value, after an acquire-release fence.
+    /// Can be used simultaneously with `get_ref()`s or other `get_mut()`s.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable reference to the wrapped value.
+    /// 
+    /// # Safety
+    /// 
+    /// The returned reference aliases every other reference obtained from this `MultiSync`.
+    /// The caller must make sure that no other thread accesses the wrapped value while it is in use.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiSync;
+    /// let multisync = MultiSync::new(10);
+    /// 
+    /// let a = unsafe {multisync.get_mut()};
+    /// let b = unsafe {multisync.get_mut()};
+    /// *a += 1;
+    /// *b += 2;
+    /// assert_eq!(multisync.unwrap(), 13);
+    /// ```
+    /// 
+    pub unsafe fn get_mut(&self) -> &mut T {
+        atomic::fence(Ordering::AcqRel);
+        return &mut *self.0.get();
+    }
+
+    /// Return the wrapped value and drop the `MultiSync`.
+    /// 
+    /// # Returns
+    /// 
+    /// The value that was wrapped.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiSync;
+    /// let multisync = MultiSync::new(10);
+    /// 
+    /// assert_eq!(multisync.unwrap(), 10);
+    /// ```
+    /// 
+    pub fn unwrap(self) -> T {
+        return self.0.into_inner();
+    }
+
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::thread;
+
+    // Every scope joins its thread before the next one starts, so the threads never touch the value at the same time.
+    #[test]
+    fn threads() {unsafe {
+        let a = 10;
+        let b = 10;
+        let c = 100;
+        let d = 1;
+
+        let multisync = MultiSync::new(a);
+
+        for _ in 0..b {
+            thread::scope(|scope| {
+                scope.spawn(|| {
+                    let mutref = multisync.get_mut();
+                    for _ in 0..c {
+                        *mutref += d;
+                    }
+                });
+            });
+        }
+
+        // Read through a fresh reference, taken after every writer has joined.
+        assert_eq!(*multisync.get_ref(), a + b * c * d);
+        assert_eq!(multisync.unwrap(), a + b * c * d);
+    }}
+
+    #[test]
+    fn send() {
+        let multisync = MultiSync::new(vec![1]);
+
+        let multisync = thread::spawn(move || {
+            unsafe {multisync.get_mut().push(2)};
+            return multisync;
+        }).join().unwrap();
+        assert_eq!(multisync.unwrap(), vec![1, 2]);
+    }
+
+}
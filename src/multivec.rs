@@ -0,0 +1,264 @@
+use std::slice;
+
+use crate::MultiRef;
+
+
+/// A `Vec` whose elements can have multiple mutable references at once, e.g. for graph or ECS-style code
+/// that needs two element handles where `split_at_mut` does not fit.
+/// 
+/// # Broken Borrow Checker Rules
+/// 
+/// * [ ] Variables must be initialised before use.
+/// * [ ] Values can not be moved more than once.
+/// * [ ] Values can not be moved while borrowed.
+/// * [x] Values can not be accessed while mutably borrowed.
+/// * [x] Values can not be mutated while immutably borrowed.
+/// 
+/// # Generics
+/// 
+/// * `T` : The type of the elements.
+/// 
+/// # Warning
+/// 
+/// * This structure is not thread safe.
+/// * You are responsible for preventing undefined behaviour.
+/// * IN MOST CASES THIS SHOULD NOT BE USED DUE TO THE UNPREDICTABLE AND DANGEROUS NATURE OF THIS SYSTEM.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::MultiVec;
+/// let multivec = MultiVec::new(vec![1, 2, 3]);
+/// 
+/// let (a, b) = unsafe {multivec.get_two_mut(0, 2)};
+/// *a += *b;
+/// assert_eq!(multivec.into_vec(), vec![4, 2, 3]);
+/// ```
+/// 
+pub struct MultiVec<T>(MultiRef<Vec<T>>);
+
+impl<T> MultiVec<T> {
+
+    /// Create a new `MultiVec` instance.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `elements` : The elements to wrap.
+    /// 
+    /// # Returns
+    /// 
+    /// The created `MultiVec` instance.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiVec;
+    /// let multivec = MultiVec::new(vec![1, 2, 3]);
+    /// ```
+    /// 
+    pub fn new(elements : Vec<T>) -> MultiVec<T> {
+        return MultiVec(MultiRef::new(elements));
+    }
+
+    /// Get the number of elements.
+    /// 
+    /// # Returns
+    /// 
+    /// The number of elements in the vector.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiVec;
+    /// let multivec = MultiVec::new(vec![1, 2, 3]);
+    /// 
+    /// assert_eq!(multivec.len(), 3);
+    /// ```
+    /// 
+    pub fn len(&self) -> usize {
+        return self.0.len();
+    }
+
+    /// Check whether there are no elements.
+    /// 
+    /// # Returns
+    /// 
+    /// `true` if the vector is empty.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiVec;
+    /// let multivec = MultiVec::<i32>::new(Vec::new());
+    /// 
+    /// assert!(multivec.is_empty());
+    /// ```
+    /// 
+    pub fn is_empty(&self) -> bool {
+        return self.0.is_empty();
+    }
+
+    /// Append an element. This takes `&mut self`, so it can not invalidate any element references that are still alive.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `element` : The element to append.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiVec;
+    /// let mut multivec = MultiVec::new(vec![1]);
+    /// 
+    /// multivec.push(2);
+    /// assert_eq!(multivec.into_vec(), vec![1, 2]);
+    /// ```
+    /// 
+    pub fn push(&mut self, element : T) {
+        self.0.push(element);
+    }
+
+    /// Get mutable references to two elements at once. The indices may be equal.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `i` : The index of the first element.
+    /// * `j` : The index of the second element.
+    /// 
+    /// # Returns
+    /// 
+    /// Mutable references to the elements at `i` and `j`.
+    /// 
+    /// # Panics
+    /// 
+    /// If `i` or `j` is out of range.
+    /// 
+    /// # Safety
+    /// 
+    /// If `i == j`, both references point to the same element.
+    /// The references alias every other reference obtained from this `MultiVec`, the same as with `MultiRef::get_mut()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiVec;
+    /// let multivec = MultiVec::new(vec![1, 2]);
+    /// 
+    /// let (a, b) = unsafe {multivec.get_two_mut(1, 1)};
+    /// *a += 10;
+    /// *b += 10;
+    /// assert_eq!(multivec.into_vec(), vec![1, 22]);
+    /// ```
+    /// 
+    pub unsafe fn get_two_mut(&self, i : usize, j : usize) -> (&mut T, &mut T) {
+        let len = self.len();
+        assert!(i < len && j < len, "indices ({}, {}) out of range for length {}", i, j, len);
+        let elements = (*self.0.as_mut_ptr()).as_mut_ptr();
+        return (&mut *elements.add(i), &mut *elements.add(j));
+    }
+
+    /// Iterate over mutable references to the elements, through a shared reference.
+    /// 
+    /// # Returns
+    /// 
+    /// An iterator over the elements.
+    /// 
+    /// # Safety
+    /// 
+    /// The same as `MultiRef::<Vec<T>>::iter_mut_aliased()`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiVec;
+    /// let multivec = MultiVec::new(vec![1, 2, 3]);
+    /// 
+    /// for x in unsafe {multivec.iter_mut_aliased()} {
+    ///     *x *= 10;
+    /// }
+    /// assert_eq!(multivec.into_vec(), vec![10, 20, 30]);
+    /// ```
+    /// 
+    pub unsafe fn iter_mut_aliased(&self) -> slice::IterMut<'_, T> {
+        return self.0.iter_mut_aliased();
+    }
+
+    /// Return the elements and drop the `MultiVec`.
+    /// 
+    /// # Returns
+    /// 
+    /// The wrapped `Vec`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::MultiVec;
+    /// let multivec = MultiVec::new(vec![1, 2, 3]);
+    /// 
+    /// assert_eq!(multivec.into_vec(), vec![1, 2, 3]);
+    /// ```
+    /// 
+    pub fn into_vec(self) -> Vec<T> {
+        return self.0.unwrap();
+    }
+
+}
+
+/// Collect the elements of an iterator into a `MultiVec`.
+impl<T> FromIterator<T> for MultiVec<T> {
+    fn from_iter<I : IntoIterator<Item = T>>(iter : I) -> MultiVec<T> {
+        return MultiVec::new(iter.into_iter().collect());
+    }
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Body {
+        pub position : i32,
+        pub velocity : i32
+    }
+
+    #[test]
+    fn get_two_mut() {unsafe {
+        let bodies = MultiVec::new(vec![
+            Body {position : 0, velocity : 1},
+            Body {position : 10, velocity : -1}
+        ]);
+
+        // Two distinct elements.
+        let (a, b) = bodies.get_two_mut(0, 1);
+        a.velocity += b.velocity;
+        b.position += a.position;
+        assert_eq!((a.velocity, b.position), (0, 10));
+
+        // The same element twice.
+        let (a, b) = bodies.get_two_mut(0, 0);
+        a.position += 5;
+        b.position += 5;
+        assert_eq!(a.position, 10);
+
+        for body in bodies.iter_mut_aliased() {
+            let (first, _) = bodies.get_two_mut(0, 0);
+            body.velocity = first.position;
+        }
+        let bodies = bodies.into_vec();
+        assert_eq!(bodies[0].velocity, 10);
+        assert_eq!(bodies[1].velocity, 10);
+    }}
+
+    #[test]
+    #[should_panic]
+    fn get_two_mut_out_of_range() {unsafe {
+        let mut multivec = (0..3).collect::<MultiVec<i32>>();
+        multivec.push(3);
+        assert_eq!(multivec.len(), 4);
+        multivec.get_two_mut(0, 4);
+    }}
+
+}
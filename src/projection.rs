@@ -0,0 +1,192 @@
+use crate::MultiRef;
+
+
+/// A reusable path from a value to one of its parts, for repeated access into deep structures inside a `MultiRef`.
+/// 
+/// # Generics
+/// 
+/// * `'a` : How long the projection closure is valid for. Paths built from non-capturing closures can be `'static`.
+/// * `T`  : The type the path starts at. It may borrow data.
+/// * `U`  : The type the path leads to.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::{MultiRef, ProjectionPath};
+/// struct Inner {
+///     pub value : i32
+/// }
+/// struct Outer {
+///     pub inner : Inner
+/// }
+/// let outer = MultiRef::new(Outer {inner : Inner {value : 1}});
+/// 
+/// let value = ProjectionPath::new(|outer : &mut Outer| &mut outer.inner.value);
+/// unsafe {*value.get_mut(&outer) += 1};
+/// unsafe {*value.get_mut(&outer) += 1};
+/// assert_eq!(outer.inner.value, 3);
+/// ```
+/// 
+// Steps work on raw pointers, so composing paths does not need `U` to outlive every possible borrow of `T`.
+// `project()` turns the result back into a reference borrowed from its argument.
+pub struct ProjectionPath<'a, T, U>(Box<dyn Fn(*mut T) -> *mut U + 'a>);
+
+impl<'a, T, U> ProjectionPath<'a, T, U> {
+
+    /// Create a new `ProjectionPath` from a projection closure.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `f` : Called with a mutable reference to the value, returns a mutable reference to the part.
+    /// 
+    /// # Returns
+    /// 
+    /// The created `ProjectionPath`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::ProjectionPath;
+    /// let first = ProjectionPath::new(|pair : &mut (i32, bool)| &mut pair.0);
+    /// ```
+    /// 
+    pub fn new(f : impl Fn(&mut T) -> &mut U + 'a) -> ProjectionPath<'a, T, U> {
+        return ProjectionPath(Box::new(move |value : *mut T| f(unsafe {&mut *value}) as *mut U));
+    }
+
+    /// Extend the path further into the part it leads to.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `next` : The path from this path's end to the new end.
+    /// 
+    /// # Returns
+    /// 
+    /// A path from this path's start to `next`'s end.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::{MultiRef, ProjectionPath};
+    /// let multiref = MultiRef::new((1, (2, 3)));
+    /// 
+    /// let inner = ProjectionPath::new(|outer : &mut (i32, (i32, i32))| &mut outer.1);
+    /// let last  = inner.then(ProjectionPath::new(|inner : &mut (i32, i32)| &mut inner.1));
+    /// unsafe {*last.get_mut(&multiref) = 30};
+    /// assert_eq!(multiref.unwrap(), (1, (2, 30)));
+    /// ```
+    /// 
+    pub fn then<V>(self, next : ProjectionPath<'a, U, V>) -> ProjectionPath<'a, T, V> where T : 'a, U : 'a, V : 'a {
+        return ProjectionPath(Box::new(move |value : *mut T| (next.0)((self.0)(value))));
+    }
+
+    /// Follow the path through a `MultiRef`.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `container` : The `MultiRef` to project into.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable reference to the part of the wrapped value this path leads to.
+    /// 
+    /// # Safety
+    /// 
+    /// The returned reference aliases every other reference obtained from `container`, the same as with `MultiRef::get_mut()`.
+    /// 
+    pub unsafe fn get_mut<'l>(&self, container : &'l MultiRef<T>) -> &'l mut U {
+        return self.project(container.get_mut());
+    }
+
+    /// Follow the path through a plain mutable reference.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `value` : The value to project into.
+    /// 
+    /// # Returns
+    /// 
+    /// A mutable reference to the part of `value` this path leads to.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use pholib::ProjectionPath;
+    /// let mut pair = (1, false);
+    /// 
+    /// let flag = ProjectionPath::new(|pair : &mut (i32, bool)| &mut pair.1);
+    /// *flag.project(&mut pair) = true;
+    /// assert_eq!(pair, (1, true));
+    /// ```
+    /// 
+    pub fn project<'l>(&self, value : &'l mut T) -> &'l mut U {
+        // The step only reborrows from `value`, so the part stays valid for as long as `value` was borrowed.
+        return unsafe {&mut *(self.0)(value)};
+    }
+
+}
+
+
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Engine {
+        pub rpm : u32
+    }
+
+    struct Car {
+        pub name   : &'static str,
+        pub engine : Engine
+    }
+
+    struct Garage {
+        pub cars : Vec<Car>
+    }
+
+    #[test]
+    fn nested() {unsafe {
+        let garage = MultiRef::new(Garage {cars : vec![
+            Car {name : "a", engine : Engine {rpm : 1000}},
+            Car {name : "b", engine : Engine {rpm : 2000}}
+        ]});
+        let second = ProjectionPath::new(|garage : &mut Garage| &mut garage.cars[1]);
+        let rpm = second.then(ProjectionPath::new(|car : &mut Car| &mut car.engine.rpm));
+        *rpm.get_mut(&garage) += 500;
+        *rpm.get_mut(&garage) *= 2;
+        assert_eq!(garage.get_ref().cars[1].engine.rpm, 5000);
+        assert_eq!(garage.get_ref().cars[0].engine.rpm, 1000);
+
+        let other = MultiRef::new(Garage {cars : vec![
+            Car {name : "c", engine : Engine {rpm : 0}},
+            Car {name : "d", engine : Engine {rpm : 1}}
+        ]});
+        *rpm.get_mut(&other) = 7;
+        assert_eq!(other.cars[1].name, "d");
+        assert_eq!(other.cars[1].engine.rpm, 7);
+        assert_eq!(garage.get_ref().cars[1].name, "b");
+    }}
+
+    struct Parser<'l> {
+        pub input    : &'l str,
+        pub position : usize
+    }
+
+    #[test]
+    fn borrowed_payload() {unsafe {
+        let input = String::from("key=value");
+        let parsers = MultiRef::new(vec![Parser {input : &input, position : 0}]);
+        let index = 0;
+
+        // Both the payload and the closure borrow locals, so neither is `'static`.
+        let first = ProjectionPath::new(|parsers : &mut Vec<Parser>| &mut parsers[index]);
+        let position = first.then(ProjectionPath::new(|parser : &mut Parser| &mut parser.position));
+        *position.get_mut(&parsers) = input.find('=').unwrap() + 1;
+        let parser = &parsers.get_ref()[0];
+        assert_eq!(&parser.input[parser.position ..], "value");
+    }}
+
+}
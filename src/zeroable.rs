@@ -0,0 +1,46 @@
+/// Marks types for which a value with every byte set to zero is valid.
+/// Used by `MultiRef::zeroed()` and `MultiBox::zeroed()` to create zeroed values without `unsafe` at the call site.
+/// 
+/// Implemented for integers, floats, `bool`, `char`, raw pointers, `()`, and arrays and tuples of `Zeroable` types.
+/// 
+/// # Safety
+/// 
+/// All-zero bytes must be a valid value of the type, with the same requirements as `std::mem::zeroed`.
+/// Structs made only of `Zeroable` fields can implement it.
+/// References, `Box`, `Vec`, `String`, function pointers, `NonZero*` types and anything else with a non-null or other invariant must not.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use pholib::{MultiRef, Zeroable};
+/// struct Sample {
+///     pub time  : u64,
+///     pub value : f32
+/// }
+/// unsafe impl Zeroable for Sample {}
+/// 
+/// let sample = MultiRef::<Sample>::zeroed();
+/// assert_eq!((sample.time, sample.value), (0, 0.0));
+/// ```
+/// 
+/// Types with invariants are rejected.
+/// ```compile_fail
+/// use pholib::MultiRef;
+/// let reference = MultiRef::<&i32>::zeroed();
+/// ```
+/// 
+pub unsafe trait Zeroable {}
+
+macro_rules! zeroable {
+    ($($ty:ty),* $(,)?) => {
+        $(unsafe impl Zeroable for $ty {})*
+    };
+}
+
+zeroable!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char, ());
+
+unsafe impl<T> Zeroable for *const T {}
+unsafe impl<T> Zeroable for *mut T {}
+unsafe impl<T : Zeroable, const N : usize> Zeroable for [T; N] {}
+unsafe impl<A : Zeroable, B : Zeroable> Zeroable for (A, B) {}
+unsafe impl<A : Zeroable, B : Zeroable, C : Zeroable> Zeroable for (A, B, C) {}